@@ -0,0 +1,330 @@
+use crate::download;
+use crate::mirror::Mirror;
+use crate::retry::RetryPolicy;
+use crate::{ChromeForTestingArtifact, ChromeForTestingManagerError};
+use chrome_for_testing::{Platform, Version};
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use rootcause::{Report, prelude::ResultExt};
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use tokio::io::AsyncRead;
+
+/// An artifact archive to retrieve through an [`ArtifactSource`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactRequest {
+    pub(crate) artifact: ChromeForTestingArtifact,
+    pub(crate) version: Version,
+    pub(crate) platform: Platform,
+    pub(crate) url: String,
+}
+
+impl ArtifactRequest {
+    /// The requested artifact.
+    #[must_use]
+    pub const fn artifact(&self) -> ChromeForTestingArtifact {
+        self.artifact
+    }
+
+    /// The version of the requested artifact.
+    #[must_use]
+    pub const fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The platform of the requested artifact.
+    #[must_use]
+    pub const fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    /// The upstream download URL listed in the release index.
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The file name of the archive, e.g. `chromedriver-linux64.zip`.
+    #[must_use]
+    pub fn file_name(&self) -> String {
+        format!("{}-{}.zip", self.artifact, self.platform)
+    }
+}
+
+/// Where a [`crate::ChromeForTestingManager`] retrieves artifact ZIP archives from.
+///
+/// By default, the manager downloads the archives over HTTP, honoring its [`Mirror`]. Pass
+/// another source to [`crate::ChromeForTestingManager::with_artifact_source`] to feed archives from
+/// a build cache or to install fixture archives in tests. Retrieved archives are validated and
+/// extracted the same way, whatever their source.
+pub trait ArtifactSource: Debug + Send + Sync {
+    /// Write the ZIP archive requested by `request` into `destination`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the archive cannot be retrieved or written.
+    fn fetch<'a>(
+        &'a self,
+        request: &'a ArtifactRequest,
+        destination: &'a mut tokio::fs::File,
+    ) -> BoxFuture<'a, Result<(), Report<ChromeForTestingManagerError>>>;
+
+    /// Where the archive requested by `request` is retrieved from, e.g. a URL or a path. Used in
+    /// log and error messages.
+    fn location(&self, request: &ArtifactRequest) -> String;
+
+    /// Whether [`Self::fetch`] retries failures itself. Otherwise, the manager retries a failed
+    /// fetch according to its [`RetryPolicy`], starting over with an empty file. `false` by
+    /// default.
+    fn retries_failures(&self) -> bool {
+        false
+    }
+}
+
+/// Downloads artifact archives over HTTP from their upstream URL, rewritten by a [`Mirror`].
+///
+/// Failed requests are retried according to its [`RetryPolicy`], resuming interrupted downloads
+/// where possible.
+#[derive(Debug, Clone, Default)]
+pub struct HttpArtifactSource {
+    client: reqwest::Client,
+    mirror: Mirror,
+    retry_policy: RetryPolicy,
+}
+
+impl HttpArtifactSource {
+    /// A source downloading from Google's hosts.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send requests through `client`, e.g. one configured with a proxy or custom certificates.
+    #[must_use]
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Download from `mirror` instead of Google's hosts.
+    #[must_use]
+    pub fn with_mirror(mut self, mirror: Mirror) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// Retry failed requests according to `policy` instead of [`RetryPolicy::default`].
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+}
+
+impl ArtifactSource for HttpArtifactSource {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a ArtifactRequest,
+        destination: &'a mut tokio::fs::File,
+    ) -> BoxFuture<'a, Result<(), Report<ChromeForTestingManagerError>>> {
+        async move {
+            let url = self.location(request);
+            download::download_http(
+                &self.client,
+                &url,
+                destination,
+                request.artifact,
+                &self.retry_policy,
+            )
+            .await
+        }
+        .boxed()
+    }
+
+    fn location(&self, request: &ArtifactRequest) -> String {
+        self.mirror.rewrite_artifact_url(&request.url)
+    }
+
+    fn retries_failures(&self) -> bool {
+        true
+    }
+}
+
+/// Copies pre-downloaded artifact archives from a local directory.
+///
+/// The directory is laid out like the upstream storage bucket:
+/// `<dir>/<version>/<platform>/<artifact>-<platform>.zip`, e.g.
+/// `135.0.7049.42/linux64/chromedriver-linux64.zip`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryArtifactSource {
+    dir: PathBuf,
+}
+
+impl DirectoryArtifactSource {
+    /// A source copying archives from `dir`.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory the archives are copied from.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, request: &ArtifactRequest) -> PathBuf {
+        self.dir
+            .join(request.version.to_string())
+            .join(request.platform.to_string())
+            .join(request.file_name())
+    }
+}
+
+impl ArtifactSource for DirectoryArtifactSource {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a ArtifactRequest,
+        destination: &'a mut tokio::fs::File,
+    ) -> BoxFuture<'a, Result<(), Report<ChromeForTestingManagerError>>> {
+        async move {
+            let path = self.path(request);
+            let context = || ChromeForTestingManagerError::Download {
+                artifact: request.artifact,
+                url: path.display().to_string(),
+            };
+            let mut archive = tokio::fs::File::open(&path).await.context_with(context)?;
+            tokio::io::copy(&mut archive, destination)
+                .await
+                .context_with(context)?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn location(&self, request: &ArtifactRequest) -> String {
+        self.path(request).display().to_string()
+    }
+}
+
+type ArtifactReader = Pin<Box<dyn AsyncRead + Send>>;
+
+type OpenReader =
+    dyn Fn(&ArtifactRequest) -> BoxFuture<'static, std::io::Result<ArtifactReader>> + Send + Sync;
+
+/// Reads artifact archives from async readers opened by a caller-supplied function.
+///
+/// ```
+/// # use chrome_for_testing_manager::{ArtifactRequest, ReaderArtifactSource};
+/// let source = ReaderArtifactSource::new(|request: &ArtifactRequest| {
+///     let path = format!("/var/cache/cft/{}", request.file_name());
+///     async move { tokio::fs::File::open(path).await }
+/// });
+/// ```
+pub struct ReaderArtifactSource {
+    open: Box<OpenReader>,
+}
+
+impl ReaderArtifactSource {
+    /// A source reading each archive from the reader `open` returns for its request.
+    pub fn new<F, Fut, R>(open: F) -> Self
+    where
+        F: Fn(&ArtifactRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::io::Result<R>> + Send + 'static,
+        R: AsyncRead + Send + 'static,
+    {
+        Self {
+            open: Box::new(move |request| {
+                open(request)
+                    .map(|reader| reader.map(|reader| Box::pin(reader) as ArtifactReader))
+                    .boxed()
+            }),
+        }
+    }
+}
+
+impl Debug for ReaderArtifactSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReaderArtifactSource")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ArtifactSource for ReaderArtifactSource {
+    fn fetch<'a>(
+        &'a self,
+        request: &'a ArtifactRequest,
+        destination: &'a mut tokio::fs::File,
+    ) -> BoxFuture<'a, Result<(), Report<ChromeForTestingManagerError>>> {
+        async move {
+            let context = || ChromeForTestingManagerError::Download {
+                artifact: request.artifact,
+                url: self.location(request),
+            };
+            let mut reader = (self.open)(request).await.context_with(context)?;
+            tokio::io::copy(&mut reader, destination)
+                .await
+                .context_with(context)?;
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn location(&self, request: &ArtifactRequest) -> String {
+        format!("reader:{}", request.file_name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::version;
+    use assertr::prelude::*;
+
+    fn chromedriver_request() -> ArtifactRequest {
+        ArtifactRequest {
+            artifact: ChromeForTestingArtifact::ChromeDriver,
+            version: version(135, 0, 7049, 42),
+            platform: Platform::Linux64,
+            url: "https://storage.googleapis.com/chrome-for-testing-public/135.0.7049.42/linux64/chromedriver-linux64.zip".to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn directory_source_copies_archives_laid_out_like_the_bucket() -> Result<(), Report> {
+        let archives = tempfile::tempdir()?;
+        let archive_dir = archives.path().join("135.0.7049.42/linux64");
+        std::fs::create_dir_all(&archive_dir)?;
+        std::fs::write(archive_dir.join("chromedriver-linux64.zip"), b"zip")?;
+        let source = DirectoryArtifactSource::new(archives.path());
+        let destination = tempfile::NamedTempFile::new()?;
+        let mut file = tokio::fs::File::create(destination.path()).await?;
+
+        source.fetch(&chromedriver_request(), &mut file).await?;
+
+        assert_that!(std::fs::read(destination.path())?).is_equal_to(b"zip".to_vec());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn directory_source_reports_missing_archives() -> Result<(), Report> {
+        let archives = tempfile::tempdir()?;
+        let source = DirectoryArtifactSource::new(archives.path());
+        let destination = tempfile::NamedTempFile::new()?;
+        let mut file = tokio::fs::File::create(destination.path()).await?;
+
+        let err = source
+            .fetch(&chromedriver_request(), &mut file)
+            .await
+            .expect_err("archive is missing");
+
+        assert_that!(matches!(
+            err.current_context(),
+            ChromeForTestingManagerError::Download { url, .. }
+                if url.ends_with("135.0.7049.42/linux64/chromedriver-linux64.zip")
+        ))
+        .is_true();
+        Ok(())
+    }
+}
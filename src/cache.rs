@@ -29,6 +29,11 @@ impl CacheDir {
         &self.0
     }
 
+    /// Directory holding the cached release-index documents.
+    pub fn index_dir(&self) -> PathBuf {
+        self.0.join("index")
+    }
+
     pub async fn clear(&self) -> Result<(), Report<ChromeForTestingManagerError>> {
         tracing::debug!("Clearing cache at {:?}...", self.path());
         fs::remove_dir_all(self.path()).await.context(
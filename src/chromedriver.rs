@@ -1,5 +1,10 @@
 use crate::ChromeForTestingManagerError;
+use crate::config_file::{check_workspace_config_file, workspace_defaults};
+use crate::env::EnvOverrides;
+use crate::index::DEFAULT_INDEX_TTL;
+use crate::lockfile::Lockfile;
 use crate::mgr::{ChromeBinary, ChromeForTestingManager, LoadedBrowserPackage};
+use crate::mirror::Mirror;
 use crate::output::{DriverOutputInspectors, DriverOutputListener};
 use crate::port::{Port, PortRequest};
 #[cfg(feature = "thirtyfour")]
@@ -24,7 +29,12 @@ use typed_builder::TypedBuilder;
 /// `TerminateProcess`).
 #[must_use]
 pub(crate) fn default_graceful_shutdown() -> GracefulShutdown {
-    let timeout = Duration::from_secs(3);
+    graceful_shutdown_after(Duration::from_secs(3))
+}
+
+/// Graceful-shutdown budget of `timeout` for `SIGTERM` on Unix and `CTRL_BREAK_EVENT` on Windows.
+#[must_use]
+pub(crate) fn graceful_shutdown_after(timeout: Duration) -> GracefulShutdown {
     GracefulShutdown::builder()
         .unix_sigterm(timeout)
         .windows_ctrl_break(timeout)
@@ -37,6 +47,29 @@ pub(crate) fn default_graceful_shutdown() -> GracefulShutdown {
 /// OS-assigned port, no output listener, default cache directory, 3s graceful termination budget
 /// on all systems.
 ///
+/// A `chrome-for-testing.toml` file, found in `$CARGO_MANIFEST_DIR` or any of its parents,
+/// replaces these defaults for the whole workspace. Values set through the builder take
+/// precedence over the file. Relative paths in the file are resolved against its directory.
+///
+/// The file is read once per process, when the first configuration is built. An invalid file is
+/// logged as a warning and contributes no defaults. [`Self::from_env`] and [`Chromedriver::run`]
+/// fail with its parse error.
+///
+/// ```toml
+/// version = "stable"                  # Or `latest`, `stable-1`, a milestone, or a full version.
+/// chrome-binary = "chrome-headless-shell"
+/// port = "any"                        # Or a port number.
+/// cache-dir = "target/chrome-for-testing"
+/// offline = false
+/// index-ttl-secs = 3600
+/// lockfile = true                     # Pin versions in a `chrome-for-testing.lock` next to it.
+/// shutdown-timeout-secs = 3
+///
+/// [mirror]
+/// index-base-url = "https://artifactory.example.com/cft-index"
+/// artifact-base-url = "https://artifactory.example.com/cft-artifacts"
+/// ```
+///
 /// ```no_run
 /// # use chrome_for_testing_manager::{Channel, ChromedriverRunConfig, DriverOutputListener, GracefulShutdown};
 /// # use std::time::Duration;
@@ -58,20 +91,34 @@ pub struct ChromedriverRunConfig {
     ///
     /// Accepts anything implementing `Into<VersionRequest>`, including [`Channel`] and
     /// [`crate::Version`].
-    #[builder(default = VersionRequest::LatestIn(Channel::Stable), setter(into))]
+    #[builder(
+        default = workspace_defaults()
+            .version
+            .clone()
+            .unwrap_or(VersionRequest::LatestIn(Channel::Stable)),
+        setter(into)
+    )]
     version: VersionRequest,
 
+    /// The requested `ChromeDriver` version, if it should differ from [`Self::version`], e.g. to
+    /// reproduce a driver regression with `ChromeDriver` 132 and Chrome 131. Majors more than one
+    /// apart are rejected.
+    ///
+    /// Only [`Self::version`] is pinned by the [`Self::lockfile`].
+    #[builder(default, setter(into, strip_option(fallback = chromedriver_version_opt)))]
+    chromedriver_version: Option<VersionRequest>,
+
     /// Chrome browser binary to run with `ChromeDriver`.
     ///
     /// Defaults to regular [`ChromeBinary::Chrome`]. Use [`ChromeBinary::ChromeHeadlessShell`] for
     /// headless-only environments that cannot launch the full `Chrome` application.
-    #[builder(default)]
+    #[builder(default = workspace_defaults().chrome_binary.unwrap_or_default())]
     chrome_binary: ChromeBinary,
 
     /// The requested `ChromeDriver` port.
     ///
     /// Accepts anything implementing `Into<PortRequest>`, including a bare `u16` and [`Port`].
-    #[builder(default = PortRequest::Any, setter(into))]
+    #[builder(default = workspace_defaults().port.unwrap_or(PortRequest::Any), setter(into))]
     port: PortRequest,
 
     /// Optional callback for browser-driver process output lines.
@@ -80,12 +127,42 @@ pub struct ChromedriverRunConfig {
 
     /// Optional override for the cache directory holding downloaded chrome / chromedriver
     /// artifacts. Defaults to the platform's per-user cache directory.
-    #[builder(default, setter(strip_option(fallback = cache_dir_opt)))]
+    #[builder(
+        default = workspace_defaults().cache_dir.clone(),
+        setter(strip_option(fallback = cache_dir_opt))
+    )]
     cache_dir: Option<PathBuf>,
 
+    /// Resolve versions against the installed cache contents instead of the release index and
+    /// never download anything. See [`ChromeForTestingManager::with_offline`].
+    #[builder(default = workspace_defaults().offline.unwrap_or_default())]
+    offline: bool,
+
+    /// How long a fetched release index is reused before being revalidated. Defaults to one hour.
+    /// See [`ChromeForTestingManager::with_index_ttl`].
+    #[builder(default = workspace_defaults().index_ttl.unwrap_or(DEFAULT_INDEX_TTL))]
+    index_ttl: Duration,
+
+    /// Alternative locations for the release index and artifact downloads. Defaults to Google's
+    /// hosts. See [`ChromeForTestingManager::with_mirror`].
+    #[builder(default = workspace_defaults().mirror.clone().unwrap_or_default())]
+    mirror: Mirror,
+
+    /// Optional lockfile pinning the resolved version across runs and machines. See [`Lockfile`].
+    #[builder(
+        default = workspace_defaults().lockfile.clone(),
+        setter(strip_option(fallback = lockfile_opt))
+    )]
+    lockfile: Option<Lockfile>,
+
     /// Per-platform graceful-shutdown budget applied when the [`Chromedriver`] handle is dropped
     /// or [`Chromedriver::terminate`] is called.
-    #[builder(default = default_graceful_shutdown())]
+    #[builder(
+        default = workspace_defaults()
+            .graceful_shutdown
+            .clone()
+            .unwrap_or_else(default_graceful_shutdown)
+    )]
     graceful_shutdown: GracefulShutdown,
 }
 
@@ -96,12 +173,78 @@ impl Default for ChromedriverRunConfig {
 }
 
 impl ChromedriverRunConfig {
+    /// The default configuration with overrides from `CFT_*` environment variables applied.
+    ///
+    /// Equivalent to `ChromedriverRunConfig::default().with_env_overrides()`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChromeForTestingManagerError::InvalidEnvVar`] if a variable holds an
+    /// unparseable value, or [`ChromeForTestingManagerError::ReadConfigFile`] if the workspace
+    /// config file is invalid.
+    pub fn from_env() -> Result<Self, Report<ChromeForTestingManagerError>> {
+        check_workspace_config_file()?;
+        Self::default().with_env_overrides()
+    }
+
+    /// Override parts of this configuration through environment variables, e.g. from CI job
+    /// definitions, without recompiling tests.
+    ///
+    /// - `CFT_VERSION` overrides [`Self::version`]: `latest`, `stable`, `beta`, `dev`, `canary`,
+    ///   `stable-N` for `N` milestones behind stable, `system` to match the installed Chrome,
+    ///   `installed` for the newest installed version, a milestone like `131`, a full version like
+    ///   `131.0.6778.204`, or a comma-separated list of these to try in order.
+    /// - `CFT_CHANNEL` overrides [`Self::version`] with the latest release of `stable`, `beta`,
+    ///   `dev` or `canary`. Conflicts with `CFT_VERSION`.
+    /// - `CFT_CHROME_BINARY` overrides [`Self::chrome_binary`]: `chrome` or
+    ///   `chrome-headless-shell`.
+    /// - `CFT_CACHE_DIR` overrides [`Self::cache_dir`].
+    /// - `CFT_PORT` overrides [`Self::port`]: a port number or `any`.
+    ///
+    /// Unset and empty variables leave the configured value untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChromeForTestingManagerError::InvalidEnvVar`] if a variable holds an
+    /// unparseable value.
+    pub fn with_env_overrides(self) -> Result<Self, Report<ChromeForTestingManagerError>> {
+        Ok(self.with_overrides(EnvOverrides::from_env()?))
+    }
+
+    fn with_overrides(mut self, overrides: EnvOverrides) -> Self {
+        let EnvOverrides {
+            version,
+            chrome_binary,
+            cache_dir,
+            port,
+        } = overrides;
+        if let Some(version) = version {
+            self.version = version;
+        }
+        if let Some(chrome_binary) = chrome_binary {
+            self.chrome_binary = chrome_binary;
+        }
+        if let Some(cache_dir) = cache_dir {
+            self.cache_dir = Some(cache_dir);
+        }
+        if let Some(port) = port {
+            self.port = port;
+        }
+        self
+    }
+
     /// The requested `Chrome` / `ChromeDriver` version.
     #[must_use]
     pub const fn version(&self) -> &VersionRequest {
         &self.version
     }
 
+    /// The requested `ChromeDriver` version, if it differs from [`Self::version`].
+    #[must_use]
+    pub const fn chromedriver_version(&self) -> Option<&VersionRequest> {
+        self.chromedriver_version.as_ref()
+    }
+
     /// The browser package to use with `ChromeDriver`.
     #[must_use]
     pub const fn chrome_binary(&self) -> ChromeBinary {
@@ -126,6 +269,30 @@ impl ChromedriverRunConfig {
         self.cache_dir.as_deref()
     }
 
+    /// Whether offline mode is enabled.
+    #[must_use]
+    pub const fn offline(&self) -> bool {
+        self.offline
+    }
+
+    /// How long a fetched release index is reused before being revalidated.
+    #[must_use]
+    pub const fn index_ttl(&self) -> Duration {
+        self.index_ttl
+    }
+
+    /// The configured release-index and artifact mirror.
+    #[must_use]
+    pub const fn mirror(&self) -> &Mirror {
+        &self.mirror
+    }
+
+    /// The configured version lockfile, if any.
+    #[must_use]
+    pub const fn lockfile(&self) -> Option<&Lockfile> {
+        self.lockfile.as_ref()
+    }
+
     /// The graceful-shutdown budget used when terminating the `ChromeDriver` process.
     #[must_use]
     pub const fn graceful_shutdown(&self) -> &GracefulShutdown {
@@ -202,6 +369,10 @@ impl Chromedriver {
     pub async fn run(
         config: ChromedriverRunConfig,
     ) -> Result<Chromedriver, Report<ChromeForTestingManagerError>> {
+        // The config was built from the workspace defaults, which are silently empty when the
+        // config file is invalid.
+        check_workspace_config_file()?;
+
         // Assert that async-drop will work.
         // This is the only way of constructing a `Chromedriver` instance,
         // so it's safe to do this here.
@@ -217,9 +388,26 @@ impl Chromedriver {
         let mgr = match config.cache_dir {
             Some(cache_dir) => ChromeForTestingManager::new_with_cache_dir(cache_dir)?,
             None => ChromeForTestingManager::new()?,
+        }
+        .with_offline(config.offline)
+        .with_index_ttl(config.index_ttl)
+        .with_mirror(config.mirror);
+        let mgr = match config.lockfile {
+            Some(lockfile) => mgr.with_lockfile(lockfile),
+            None => mgr,
         };
         let selected = mgr.resolve_version(config.version).await?;
-        let loaded = mgr.download_one(&selected, config.chrome_binary).await?;
+        let loaded = match config.chromedriver_version {
+            Some(chromedriver_version) => {
+                let chromedriver = mgr.resolve_unlocked_version(chromedriver_version).await?;
+                mgr.download_one(&selected, &chromedriver, config.chrome_binary)
+                    .await?
+            }
+            None => {
+                mgr.download_one(&selected, &selected, config.chrome_binary)
+                    .await?
+            }
+        };
         let graceful_shutdown = config.graceful_shutdown;
         let (process_handle, actual_port, output_inspectors) = mgr
             .launch_chromedriver(
@@ -343,6 +531,28 @@ mod tests {
         assert_that!(config.chrome_binary()).is_equal_to(ChromeBinary::Chrome);
         assert_that!(config.port()).is_equal_to(PortRequest::Any);
         assert_that!(config.output_listener()).is_none();
+        assert_that!(config.offline()).is_false();
+        assert_that!(config.index_ttl()).is_equal_to(Duration::from_secs(60 * 60));
+        assert_that!(config.lockfile()).is_none();
+    }
+
+    #[test]
+    fn env_overrides_replace_configured_values() {
+        let config = ChromedriverRunConfig::builder()
+            .version(Channel::Beta)
+            .port(8080u16)
+            .build()
+            .with_overrides(EnvOverrides {
+                version: Some(VersionRequest::Milestone(131)),
+                chrome_binary: None,
+                cache_dir: Some(PathBuf::from("/var/cache/cft")),
+                port: Some(PortRequest::Any),
+            });
+
+        assert_that!(config.version()).is_equal_to(VersionRequest::Milestone(131));
+        assert_that!(config.chrome_binary()).is_equal_to(ChromeBinary::Chrome);
+        assert_that!(config.cache_dir()).is_equal_to(Some(Path::new("/var/cache/cft")));
+        assert_that!(config.port()).is_equal_to(PortRequest::Any);
     }
 
     #[test]
@@ -0,0 +1,269 @@
+use crate::chromedriver::graceful_shutdown_after;
+use crate::env::{parse_chrome_binary, parse_port_request, parse_version_request};
+use crate::lockfile::{LOCKFILE_NAME, Lockfile};
+use crate::mgr::ChromeBinary;
+use crate::mirror::Mirror;
+use crate::port::PortRequest;
+use crate::version::VersionRequest;
+use crate::{ChromeForTestingManagerError, workspace};
+use rootcause::{Report, prelude::ResultExt, report};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio_process_tools::GracefulShutdown;
+
+/// File name of the workspace config file, discovered by walking up from `$CARGO_MANIFEST_DIR`.
+pub(crate) const CONFIG_FILE_NAME: &str = "chrome-for-testing.toml";
+
+/// The raw contents of a `chrome-for-testing.toml` file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct RawConfigFile {
+    version: Option<String>,
+    chrome_binary: Option<String>,
+    port: Option<RawPort>,
+    cache_dir: Option<PathBuf>,
+    offline: Option<bool>,
+    index_ttl_secs: Option<u64>,
+    lockfile: Option<bool>,
+    mirror: Option<Mirror>,
+    shutdown_timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawPort {
+    Number(u16),
+    Text(String),
+}
+
+/// [`crate::ChromedriverRunConfig`] defaults read from a `chrome-for-testing.toml` file.
+///
+/// Relative paths are resolved against the directory containing the file.
+#[derive(Debug, Default)]
+pub(crate) struct ConfigFile {
+    pub(crate) version: Option<VersionRequest>,
+    pub(crate) chrome_binary: Option<ChromeBinary>,
+    pub(crate) port: Option<PortRequest>,
+    pub(crate) cache_dir: Option<PathBuf>,
+    pub(crate) offline: Option<bool>,
+    pub(crate) index_ttl: Option<Duration>,
+    pub(crate) lockfile: Option<Lockfile>,
+    pub(crate) mirror: Option<Mirror>,
+    pub(crate) graceful_shutdown: Option<GracefulShutdown>,
+}
+
+impl ConfigFile {
+    /// Read and parse the config file at `path`.
+    pub(crate) fn load(path: &Path) -> Result<Self, Report<ChromeForTestingManagerError>> {
+        let contents = std::fs::read_to_string(path).context(
+            ChromeForTestingManagerError::ReadConfigFile {
+                path: path.to_owned(),
+            },
+        )?;
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        Self::parse(&contents, base_dir).context(ChromeForTestingManagerError::ReadConfigFile {
+            path: path.to_owned(),
+        })
+    }
+
+    fn parse(
+        contents: &str,
+        base_dir: &Path,
+    ) -> Result<Self, Report<ChromeForTestingManagerError>> {
+        let raw = toml::from_str::<RawConfigFile>(contents)
+            .context(ChromeForTestingManagerError::InvalidConfigValue { key: "<file>" })?;
+
+        let version = raw
+            .version
+            .map(|value| {
+                parse_version_request(&value).ok_or_else(|| {
+                    report!(ChromeForTestingManagerError::InvalidConfigValue { key: "version" })
+                        .attach(format!(
+                            "expected `latest`, a channel name, `stable-N`, `system`, `installed`, a milestone, a full version, or a comma-separated list of these; got {value:?}"
+                        ))
+                })
+            })
+            .transpose()?;
+        let chrome_binary = raw
+            .chrome_binary
+            .map(|value| {
+                parse_chrome_binary(&value).ok_or_else(|| {
+                    report!(ChromeForTestingManagerError::InvalidConfigValue {
+                        key: "chrome-binary"
+                    })
+                    .attach(format!(
+                        "expected `chrome` or `chrome-headless-shell`; got {value:?}"
+                    ))
+                })
+            })
+            .transpose()?;
+        let port = raw
+            .port
+            .map(|port| match port {
+                RawPort::Number(port) => Ok(PortRequest::from(port)),
+                RawPort::Text(value) => parse_port_request(&value).ok_or_else(|| {
+                    report!(ChromeForTestingManagerError::InvalidConfigValue { key: "port" })
+                        .attach(format!("expected a port number or `any`; got {value:?}"))
+                }),
+            })
+            .transpose()?;
+
+        Ok(Self {
+            version,
+            chrome_binary,
+            port,
+            cache_dir: raw.cache_dir.map(|cache_dir| base_dir.join(cache_dir)),
+            offline: raw.offline,
+            index_ttl: raw.index_ttl_secs.map(Duration::from_secs),
+            lockfile: raw
+                .lockfile
+                .filter(|enabled| *enabled)
+                .map(|_| Lockfile::new(base_dir.join(LOCKFILE_NAME))),
+            mirror: raw.mirror,
+            graceful_shutdown: raw
+                .shutdown_timeout_secs
+                .map(|secs| graceful_shutdown_after(Duration::from_secs(secs))),
+        })
+    }
+}
+
+/// The workspace config file, loaded once per process. `None` if there is none.
+///
+/// On failure, only the path of the invalid file is kept. [`check_workspace_config_file`]
+/// reloads it to report the error.
+fn workspace_config_file() -> &'static Result<Option<ConfigFile>, PathBuf> {
+    static CONFIG_FILE: OnceLock<Result<Option<ConfigFile>, PathBuf>> = OnceLock::new();
+    CONFIG_FILE.get_or_init(|| {
+        let Some(path) = workspace::search_start()
+            .and_then(|start| workspace::find_upwards(&start, CONFIG_FILE_NAME))
+        else {
+            return Ok(None);
+        };
+        match ConfigFile::load(&path) {
+            Ok(config) => {
+                tracing::debug!("Using defaults from {path:?}.");
+                Ok(Some(config))
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Ignoring the defaults of invalid config file {path:?}. `ChromedriverRunConfig::from_env` and `Chromedriver::run` will fail: {err}"
+                );
+                Err(path)
+            }
+        }
+    })
+}
+
+/// Fail if the workspace config file exists but could not be loaded.
+pub(crate) fn check_workspace_config_file() -> Result<(), Report<ChromeForTestingManagerError>> {
+    match workspace_config_file() {
+        Ok(_) => Ok(()),
+        Err(path) => ConfigFile::load(path).map(|_| ()),
+    }
+}
+
+/// The defaults read from the workspace config file. Empty if there is none or it is invalid.
+///
+/// An invalid file is reported by [`check_workspace_config_file`].
+pub(crate) fn workspace_defaults() -> &'static ConfigFile {
+    static EMPTY: ConfigFile = ConfigFile {
+        version: None,
+        chrome_binary: None,
+        port: None,
+        cache_dir: None,
+        offline: None,
+        index_ttl: None,
+        lockfile: None,
+        mirror: None,
+        graceful_shutdown: None,
+    };
+    match workspace_config_file() {
+        Ok(Some(config)) => config,
+        Ok(None) | Err(_) => &EMPTY,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::version;
+    use assertr::prelude::*;
+
+    #[test]
+    fn parses_all_settings() {
+        let config = ConfigFile::parse(
+            r#"
+            version = "131.0.6778.204"
+            chrome-binary = "chrome-headless-shell"
+            port = 9515
+            cache-dir = "target/chrome-for-testing"
+            offline = true
+            index-ttl-secs = 60
+            lockfile = true
+            shutdown-timeout-secs = 5
+
+            [mirror]
+            index-base-url = "https://artifactory.example.com/cft-index"
+            "#,
+            Path::new("/workspace"),
+        )
+        .expect("valid config file");
+
+        assert_that!(config.version)
+            .is_equal_to(Some(VersionRequest::Fixed(version(131, 0, 6778, 204))));
+        assert_that!(config.chrome_binary).is_equal_to(Some(ChromeBinary::ChromeHeadlessShell));
+        assert_that!(config.port).is_equal_to(Some(PortRequest::from(9515)));
+        assert_that!(config.cache_dir)
+            .is_equal_to(Some(PathBuf::from("/workspace/target/chrome-for-testing")));
+        assert_that!(config.offline).is_equal_to(Some(true));
+        assert_that!(config.index_ttl).is_equal_to(Some(Duration::from_secs(60)));
+        assert_that!(config.lockfile)
+            .is_equal_to(Some(Lockfile::new("/workspace/chrome-for-testing.lock")));
+        assert_that!(config.mirror.expect("mirror").index_base_url())
+            .is_equal_to("https://artifactory.example.com/cft-index");
+        assert_that!(config.graceful_shutdown)
+            .is_equal_to(Some(graceful_shutdown_after(Duration::from_secs(5))));
+    }
+
+    #[test]
+    fn empty_file_sets_no_defaults() {
+        let config = ConfigFile::parse("", Path::new("/workspace")).expect("valid config file");
+
+        assert_that!(config.version).is_none();
+        assert_that!(config.lockfile).is_none();
+        assert_that!(config.graceful_shutdown).is_none();
+    }
+
+    #[test]
+    fn rejects_invalid_values_and_unknown_keys() {
+        for contents in [
+            "version = \"newest\"",
+            "chrome-binary = \"firefox\"",
+            "port = \"somewhere\"",
+            "chrome_binary = \"chrome\"",
+        ] {
+            let err = ConfigFile::parse(contents, Path::new("/workspace"))
+                .expect_err("invalid config file");
+
+            assert_that!(matches!(
+                err.current_context(),
+                ChromeForTestingManagerError::InvalidConfigValue { .. }
+            ))
+            .is_true();
+        }
+    }
+
+    #[test]
+    fn loads_config_file_from_disk() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        std::fs::write(&path, "cache-dir = \"cache\"\n")?;
+
+        let config = ConfigFile::load(&path)?;
+
+        assert_that!(config.cache_dir).is_equal_to(Some(dir.path().join("cache")));
+        Ok(())
+    }
+}
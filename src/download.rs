@@ -1,55 +1,53 @@
+use crate::artifact_source::{ArtifactRequest, ArtifactSource};
+use crate::retry::RetryPolicy;
 use crate::{ChromeForTestingArtifact, ChromeForTestingManagerError};
-use rootcause::{Report, bail, prelude::ResultExt};
+use reqwest::StatusCode;
+use reqwest::header::{ACCEPT_RANGES, CONTENT_RANGE, ETAG, HeaderValue, IF_RANGE, RANGE};
+use rootcause::{Report, bail, prelude::ResultExt, report};
 use std::fs;
-use std::path::Path;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
 use tokio::time::timeout;
 use zip::ZipArchive;
 
 const MAX_DECOMPRESSED_SIZE: u128 = 2 * 1024 * 1024 * 1024; // 2 GB
 const CHUNK_TIMEOUT: Duration = Duration::from_secs(30);
 const MAX_CONSECUTIVE_STALLS: u32 = 3;
+const MAX_ATTEMPTS_WITHOUT_PROGRESS: u32 = 3;
 
-#[tracing::instrument(skip(client))]
+/// Retrieve the ZIP archive requested by `request` from `source` into `download_dir`, then extract
+/// it into `unpack_dir`. Failed retrievals are retried according to `retry`, unless the source
+/// retries failures itself.
+///
+/// Returns the paths of the extracted regular files, relative to `unpack_dir`.
+#[tracing::instrument(skip(source))]
 pub(crate) async fn download_zip(
-    client: &reqwest::Client,
-    url: &str,
+    source: &dyn ArtifactSource,
+    request: &ArtifactRequest,
     download_dir: &Path,
     unpack_dir: &Path,
-    artifact: ChromeForTestingArtifact,
-) -> Result<(), Report<ChromeForTestingManagerError>> {
-    // Initiate download and validate HTTP response.
-    tracing::info!("Downloading from {url:?}...");
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .context(ChromeForTestingManagerError::Download {
-            artifact,
-            url: url.to_owned(),
-        })?
-        .error_for_status()
-        .context(ChromeForTestingManagerError::Download {
-            artifact,
-            url: url.to_owned(),
-        })?;
+    retry: &RetryPolicy,
+) -> Result<Vec<PathBuf>, Report<ChromeForTestingManagerError>> {
+    let artifact = request.artifact;
+    tracing::info!("Downloading from {:?}...", source.location(request));
 
-    // Create new file for storage.
+    // Retrieve the archive, starting over with a new file for every attempt.
     let download_file_path = download_dir.join(format!("{artifact}.zip"));
-    let mut file = tokio::fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&download_file_path)
-        .await
-        .context(ChromeForTestingManagerError::CreateDownloadFile {
-            artifact,
-            path: download_file_path.clone(),
-        })?;
-
-    // Perform the download.
-    write_file(&mut file, response, artifact).await?;
+    if source.retries_failures() {
+        fetch_to_file(source, request, &download_file_path).await?;
+    } else {
+        retry
+            .run(
+                || ChromeForTestingManagerError::Download {
+                    artifact,
+                    url: source.location(request),
+                },
+                || fetch_to_file(source, request, &download_file_path),
+            )
+            .await?;
+    }
     tracing::info!("Download complete");
 
     // Open and validate the archive.
@@ -74,6 +72,22 @@ pub(crate) async fn download_zip(
         });
     }
 
+    // Remember the regular files to extract.
+    let mut files = Vec::with_capacity(archive.len());
+    for index in 0..archive.len() {
+        let entry =
+            archive
+                .by_index_raw(index)
+                .context(ChromeForTestingManagerError::InvalidZip {
+                    path: download_file_path.clone(),
+                })?;
+        if entry.is_file()
+            && let Some(name) = entry.enclosed_name()
+        {
+            files.push(name);
+        }
+    }
+
     // Extract.
     tracing::info!("Extracting to {unpack_dir:?}...");
     archive
@@ -91,6 +105,256 @@ pub(crate) async fn download_zip(
         },
     )?;
 
+    Ok(files)
+}
+
+/// Retrieve the archive requested by `request` from `source` into a new file at `path`.
+async fn fetch_to_file(
+    source: &dyn ArtifactSource,
+    request: &ArtifactRequest,
+    path: &Path,
+) -> Result<(), Report<ChromeForTestingManagerError>> {
+    let artifact = request.artifact;
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(path)
+        .await
+        .context(ChromeForTestingManagerError::CreateDownloadFile {
+            artifact,
+            path: path.to_owned(),
+        })?;
+    source.fetch(request, &mut file).await?;
+    file.flush()
+        .await
+        .context(ChromeForTestingManagerError::FlushDownloadFile { artifact })?;
+    Ok(())
+}
+
+/// GET `url` with `client` and write the response body into `file`.
+///
+/// An interrupted transfer, i.e. a stall or a dropped connection, keeps the bytes received so far.
+/// If the server advertised `Accept-Ranges: bytes`, the rest is requested with a `Range` request,
+/// validated through `If-Range` against the `ETag` and against the total length of the first
+/// response. Otherwise, or if the server answers with the full body, the download starts over.
+/// A body ending before the total length announced by the server counts as an interruption as well.
+/// Gives up after [`MAX_ATTEMPTS_WITHOUT_PROGRESS`] attempts not getting any further than before.
+///
+/// Every request, including the ones resuming the download, is retried according to `retry`.
+pub(crate) async fn download_http(
+    client: &reqwest::Client,
+    url: &str,
+    file: &mut tokio::fs::File,
+    artifact: ChromeForTestingArtifact,
+    retry: &RetryPolicy,
+) -> Result<(), Report<ChromeForTestingManagerError>> {
+    let context = || ChromeForTestingManagerError::Download {
+        artifact,
+        url: url.to_owned(),
+    };
+    let response = send(|| client.get(url), retry, context).await?;
+    let mut resumption = Resumption::of(&response);
+    let mut total_length = response.content_length();
+    let mut attempt = Ok(response);
+    let mut written = 0;
+    let mut furthest = 0;
+    let mut attempts_without_progress = 0;
+
+    loop {
+        let interruption = match attempt {
+            Ok(response) => match write_file(file, response, artifact, &mut written).await {
+                Ok(()) => match total_length {
+                    Some(total_length) if written < total_length => report!(context()).attach(
+                        format!("the response ended after {written} of {total_length} bytes"),
+                    ),
+                    Some(total_length) if written > total_length => {
+                        // The file changed; the next attempt starts over.
+                        resumption = None;
+                        report!(context()).attach(format!(
+                            "the response exceeded the total length of {total_length} bytes"
+                        ))
+                    }
+                    _ => return Ok(()),
+                },
+                Err(err) if is_interruption(&err) => err,
+                Err(err) => return Err(err),
+            },
+            Err(err) => err,
+        };
+
+        if written > furthest {
+            furthest = written;
+            attempts_without_progress = 0;
+        } else {
+            attempts_without_progress += 1;
+        }
+        if attempts_without_progress >= MAX_ATTEMPTS_WITHOUT_PROGRESS {
+            return Err(interruption);
+        }
+
+        let resume_from = match &resumption {
+            Some(resumption) if written > 0 => {
+                tracing::warn!("Download interrupted after {written} bytes; resuming.");
+                Some(resumption)
+            }
+            _ => {
+                tracing::warn!("Download interrupted after {written} bytes; starting over.");
+                restart(file, &mut written, artifact).await?;
+                None
+            }
+        };
+        let next = send(
+            || match resume_from {
+                Some(resumption) => resumption.resume(client.get(url), written),
+                None => client.get(url),
+            },
+            retry,
+            context,
+        )
+        .await?;
+        attempt = if next.status() == StatusCode::PARTIAL_CONTENT {
+            if resumption
+                .as_ref()
+                .is_some_and(|resumption| resumption.continues_at(&next, written))
+            {
+                total_length =
+                    total_length.or_else(|| content_range(&next).and_then(|(_, total)| total));
+                Ok(next)
+            } else {
+                // Not resumable after all; the next attempt starts over.
+                resumption = None;
+                Err(report!(context()).attach("the server answered with a mismatching range"))
+            }
+        } else {
+            // The server ignored the range or the file changed; this is the full file.
+            restart(file, &mut written, artifact).await?;
+            resumption = Resumption::of(&next);
+            total_length = next.content_length();
+            Ok(next)
+        };
+    }
+}
+
+/// Send the request built by `request`, retrying failed requests and error statuses according to
+/// `retry`.
+async fn send(
+    request: impl Fn() -> reqwest::RequestBuilder,
+    retry: &RetryPolicy,
+    context: impl Fn() -> ChromeForTestingManagerError,
+) -> Result<reqwest::Response, Report<ChromeForTestingManagerError>> {
+    retry
+        .run(&context, || async {
+            request()
+                .send()
+                .await
+                .context_with(&context)?
+                .error_for_status()
+                .context_with(&context)
+        })
+        .await
+}
+
+/// How an interrupted download can be resumed, as advertised by the server.
+#[derive(Debug)]
+struct Resumption {
+    /// A strong `ETag`, usable in `If-Range`.
+    etag: Option<HeaderValue>,
+
+    /// The total length of the file.
+    total_length: Option<u64>,
+}
+
+impl Resumption {
+    /// `None` if the server does not accept byte ranges for the file of `response`, or offers no
+    /// way to check that a range belongs to the same file.
+    fn of(response: &reqwest::Response) -> Option<Self> {
+        let headers = response.headers();
+        let accepts_ranges = headers
+            .get(ACCEPT_RANGES)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.eq_ignore_ascii_case("bytes"));
+        let etag = headers
+            .get(ETAG)
+            .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+            .cloned();
+        let total_length = response.content_length();
+        (accepts_ranges && (etag.is_some() || total_length.is_some()))
+            .then_some(Self { etag, total_length })
+    }
+
+    /// Request the bytes from `offset` on.
+    fn resume(&self, request: reqwest::RequestBuilder, offset: u64) -> reqwest::RequestBuilder {
+        let request = request.header(RANGE, format!("bytes={offset}-"));
+        match &self.etag {
+            Some(etag) => request.header(IF_RANGE, etag.clone()),
+            None => request,
+        }
+    }
+
+    /// Whether the partial `response` continues the file at `offset`.
+    fn continues_at(&self, response: &reqwest::Response, offset: u64) -> bool {
+        let Some((start, total_length)) = content_range(response) else {
+            return false;
+        };
+        let same_etag = match &self.etag {
+            Some(etag) => response
+                .headers()
+                .get(ETAG)
+                .is_none_or(|other| other == etag),
+            None => true,
+        };
+        let same_length = match (self.total_length, total_length) {
+            (Some(expected), Some(actual)) => expected == actual,
+            _ => true,
+        };
+        start == offset && same_etag && same_length
+    }
+}
+
+/// The start and total length of the `Content-Range` of `response`.
+fn content_range(response: &reqwest::Response) -> Option<(u64, Option<u64>)> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_content_range)
+}
+
+/// Parse the start and total length of a `Content-Range: bytes <start>-<end>/<total>` header.
+/// The total length is `None` if unknown (`*`).
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let (start, _end) = range.split_once('-')?;
+    let total = match total {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start.parse().ok()?, total))
+}
+
+/// Whether `err` is a transient interruption of the transfer, after which it may be resumed.
+fn is_interruption(err: &Report<ChromeForTestingManagerError>) -> bool {
+    matches!(
+        err.current_context(),
+        ChromeForTestingManagerError::Download { .. }
+            | ChromeForTestingManagerError::DownloadStalled { .. }
+    )
+}
+
+/// Discard the bytes written to `file` so far.
+async fn restart(
+    file: &mut tokio::fs::File,
+    written: &mut u64,
+    artifact: ChromeForTestingArtifact,
+) -> Result<(), Report<ChromeForTestingManagerError>> {
+    file.set_len(0)
+        .await
+        .context(ChromeForTestingManagerError::WriteDownloadFile { artifact })?;
+    file.seek(SeekFrom::Start(0))
+        .await
+        .context(ChromeForTestingManagerError::WriteDownloadFile { artifact })?;
+    *written = 0;
     Ok(())
 }
 
@@ -98,6 +362,7 @@ async fn write_file(
     file: &mut tokio::fs::File,
     mut response: reqwest::Response,
     artifact: ChromeForTestingArtifact,
+    written: &mut u64,
 ) -> Result<(), Report<ChromeForTestingManagerError>> {
     if let Some(content_length) = response.content_length() {
         #[allow(clippy::cast_precision_loss)] // Display-only; precision loss is irrelevant.
@@ -114,6 +379,7 @@ async fn write_file(
                 file.write_all(&chunk)
                     .await
                     .context(ChromeForTestingManagerError::WriteDownloadFile { artifact })?;
+                *written += chunk.len() as u64;
             }
             Ok(Ok(None)) => break,
             Ok(Err(err)) => {
@@ -141,9 +407,175 @@ async fn write_file(
         }
     }
 
-    file.flush()
-        .await
-        .context(ChromeForTestingManagerError::FlushDownloadFile { artifact })?;
-
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+    use mockito::Matcher;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    async fn download_to_vec(url: &str) -> Result<Vec<u8>, Report> {
+        let destination = tempfile::NamedTempFile::new()?;
+        let mut file = tokio::fs::File::create(destination.path()).await?;
+        download_http(
+            &reqwest::Client::new(),
+            url,
+            &mut file,
+            ChromeForTestingArtifact::Chrome,
+            &RetryPolicy::new().with_backoff(Duration::ZERO, Duration::ZERO),
+        )
+        .await?;
+        file.flush().await?;
+        Ok(std::fs::read(destination.path())?)
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resumes_interrupted_downloads_with_range_requests() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let _full = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", Matcher::Missing)
+            .with_header("accept-ranges", "bytes")
+            .with_header("etag", "\"v1\"")
+            .with_chunked_body(|writer| {
+                writer.write_all(b"hello")?;
+                writer.flush()?;
+                Err(std::io::Error::other("connection reset"))
+            })
+            .create_async()
+            .await;
+        let rest = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", "bytes=5-")
+            .match_header("if-range", "\"v1\"")
+            .with_status(206)
+            .with_header("content-range", "bytes 5-10/11")
+            .with_header("etag", "\"v1\"")
+            .with_body(" world")
+            .create_async()
+            .await;
+
+        let downloaded = download_to_vec(&format!("{}/chrome.zip", server.url())).await?;
+
+        assert_that!(downloaded).is_equal_to(b"hello world".to_vec());
+        rest.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn retries_failed_range_requests_without_starting_over() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let full = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", Matcher::Missing)
+            .with_header("accept-ranges", "bytes")
+            .with_header("etag", "\"v1\"")
+            .with_chunked_body(|writer| {
+                writer.write_all(b"hello")?;
+                writer.flush()?;
+                Err(std::io::Error::other("connection reset"))
+            })
+            .expect(1)
+            .create_async()
+            .await;
+        let unavailable = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", "bytes=5-")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let rest = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", "bytes=5-")
+            .with_status(206)
+            .with_header("content-range", "bytes 5-10/11")
+            .with_header("etag", "\"v1\"")
+            .with_body(" world")
+            .create_async()
+            .await;
+
+        let downloaded = download_to_vec(&format!("{}/chrome.zip", server.url())).await?;
+
+        assert_that!(downloaded).is_equal_to(b"hello world".to_vec());
+        full.assert_async().await;
+        unavailable.assert_async().await;
+        rest.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resumes_responses_ending_before_the_total_length() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let _full = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", Matcher::Missing)
+            .with_header("accept-ranges", "bytes")
+            .with_header("etag", "\"v1\"")
+            .with_chunked_body(|writer| {
+                writer.write_all(b"hello")?;
+                writer.flush()?;
+                Err(std::io::Error::other("connection reset"))
+            })
+            .create_async()
+            .await;
+        let _short = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", "bytes=5-")
+            .with_status(206)
+            .with_header("content-range", "bytes 5-7/11")
+            .with_body(" wo")
+            .create_async()
+            .await;
+        let rest = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", "bytes=8-")
+            .with_status(206)
+            .with_header("content-range", "bytes 8-10/11")
+            .with_body("rld")
+            .create_async()
+            .await;
+
+        let downloaded = download_to_vec(&format!("{}/chrome.zip", server.url())).await?;
+
+        assert_that!(downloaded).is_equal_to(b"hello world".to_vec());
+        rest.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn starts_over_if_the_server_does_not_accept_ranges() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let requests = AtomicUsize::new(0);
+        let mock = server
+            .mock("GET", "/chrome.zip")
+            .match_header("range", Matcher::Missing)
+            .with_chunked_body(move |writer| {
+                if requests.fetch_add(1, Ordering::SeqCst) == 0 {
+                    writer.write_all(b"hel")?;
+                    writer.flush()?;
+                    return Err(std::io::Error::other("connection reset"));
+                }
+                writer.write_all(b"hello world")
+            })
+            .expect(2)
+            .create_async()
+            .await;
+
+        let downloaded = download_to_vec(&format!("{}/chrome.zip", server.url())).await?;
+
+        assert_that!(downloaded).is_equal_to(b"hello world".to_vec());
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[test]
+    fn parses_content_ranges() {
+        assert_that!(parse_content_range("bytes 5-10/11")).is_equal_to(Some((5, Some(11))));
+        assert_that!(parse_content_range("bytes 5-10/*")).is_equal_to(Some((5, None)));
+        assert_that!(parse_content_range("items 5-10/11")).is_none();
+    }
+}
@@ -0,0 +1,292 @@
+use crate::mgr::ChromeBinary;
+use crate::port::PortRequest;
+use crate::version::VersionRequest;
+use crate::{ChromeForTestingManagerError, Version};
+use chrome_for_testing::Channel;
+use rootcause::{Report, report};
+use std::ffi::OsString;
+use std::path::PathBuf;
+
+/// Overrides the requested version. See [`parse_version_request`] for the accepted values.
+pub(crate) const CFT_VERSION: &str = "CFT_VERSION";
+
+/// Overrides the requested version with the latest release of a channel.
+pub(crate) const CFT_CHANNEL: &str = "CFT_CHANNEL";
+
+/// Overrides the browser binary: `chrome` or `chrome-headless-shell`.
+pub(crate) const CFT_CHROME_BINARY: &str = "CFT_CHROME_BINARY";
+
+/// Overrides the cache directory.
+pub(crate) const CFT_CACHE_DIR: &str = "CFT_CACHE_DIR";
+
+/// Overrides the port: a port number or `any`.
+pub(crate) const CFT_PORT: &str = "CFT_PORT";
+
+/// Configuration values overridden through `CFT_*` environment variables.
+///
+/// Empty variables are treated as unset, so CI job definitions can leave them blank. The cache
+/// directory may be any path; all other values must be valid Unicode.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct EnvOverrides {
+    pub(crate) version: Option<VersionRequest>,
+    pub(crate) chrome_binary: Option<ChromeBinary>,
+    pub(crate) cache_dir: Option<PathBuf>,
+    pub(crate) port: Option<PortRequest>,
+}
+
+impl EnvOverrides {
+    /// Read all overrides from the process environment.
+    pub(crate) fn from_env() -> Result<Self, Report<ChromeForTestingManagerError>> {
+        Self::from_lookup(|name| std::env::var_os(name))
+    }
+
+    /// Read all overrides through `lookup`, which maps a variable name to its value.
+    pub(crate) fn from_lookup(
+        lookup: impl Fn(&str) -> Option<OsString>,
+    ) -> Result<Self, Report<ChromeForTestingManagerError>> {
+        let var_os =
+            |name: &str| lookup(name).filter(|value| !value.to_string_lossy().trim().is_empty());
+        let var = |name: &'static str| {
+            var_os(name)
+                .map(|value| {
+                    value.into_string().map_err(|value| {
+                        invalid(name, value.to_string_lossy().into_owned())
+                            .attach("expected valid Unicode")
+                    })
+                })
+                .transpose()
+        };
+
+        let version = var(CFT_VERSION)?
+            .map(|value| {
+                parse_version_request(&value).ok_or_else(|| {
+                    invalid(CFT_VERSION, value).attach(
+                        "expected `latest`, a channel name, `stable-N`, `system`, `installed`, a milestone like `131`, a full version like `131.0.6778.204`, or a comma-separated list of these",
+                    )
+                })
+            })
+            .transpose()?;
+        let channel = var(CFT_CHANNEL)?
+            .map(|value| match value.trim().parse::<Channel>() {
+                Ok(channel) if channel.is_known() => Ok(channel),
+                _ => Err(invalid(CFT_CHANNEL, value)
+                    .attach("expected one of `stable`, `beta`, `dev` or `canary`")),
+            })
+            .transpose()?;
+        let version = match (version, channel) {
+            (Some(_), Some(channel)) => {
+                return Err(invalid(CFT_CHANNEL, channel.to_string()).attach(format!(
+                    "conflicts with {CFT_VERSION}; set only one of them"
+                )));
+            }
+            (version, channel) => version.or(channel.map(VersionRequest::LatestIn)),
+        };
+        let chrome_binary = var(CFT_CHROME_BINARY)?
+            .map(|value| {
+                parse_chrome_binary(&value).ok_or_else(|| {
+                    invalid(CFT_CHROME_BINARY, value)
+                        .attach("expected `chrome` or `chrome-headless-shell`")
+                })
+            })
+            .transpose()?;
+        let port = var(CFT_PORT)?
+            .map(|value| {
+                parse_port_request(&value).ok_or_else(|| {
+                    invalid(CFT_PORT, value).attach("expected a port number (0-65535) or `any`")
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            version,
+            chrome_binary,
+            cache_dir: var_os(CFT_CACHE_DIR).map(PathBuf::from),
+            port,
+        })
+    }
+}
+
+fn invalid(name: &'static str, value: String) -> Report<ChromeForTestingManagerError> {
+    report!(ChromeForTestingManagerError::InvalidEnvVar { name, value })
+}
+
+/// Parse a textual version request.
+///
+/// Accepts `latest`, a channel name (`stable`, `beta`, `dev`, `canary`), a number of milestones
+/// behind stable (`stable-2`), `system` for the milestone of the installed Chrome, `installed` for
+/// the newest installed version, a milestone (`131`), or a full version (`131.0.6778.204`).
+/// A comma-separated list of these, e.g. `canary,beta,installed`, is tried in order.
+pub(crate) fn parse_version_request(value: &str) -> Option<VersionRequest> {
+    if value.contains(',') {
+        return value
+            .split(',')
+            .map(parse_single_version_request)
+            .collect::<Option<Vec<_>>>()
+            .map(VersionRequest::FirstOf);
+    }
+    parse_single_version_request(value)
+}
+
+fn parse_single_version_request(value: &str) -> Option<VersionRequest> {
+    let value = value.trim();
+    match value.to_ascii_lowercase().as_str() {
+        "latest" => return Some(VersionRequest::Latest),
+        "stable" => return Some(VersionRequest::stable()),
+        "beta" => return Some(VersionRequest::beta()),
+        "dev" => return Some(VersionRequest::dev()),
+        "canary" => return Some(VersionRequest::canary()),
+        "system" => return Some(VersionRequest::SystemChrome),
+        "installed" => return Some(VersionRequest::LatestInstalled),
+        _ => {}
+    }
+    if let Some(n) = value
+        .to_ascii_lowercase()
+        .strip_prefix("stable-")
+        .and_then(|n| n.parse::<u32>().ok())
+    {
+        return Some(VersionRequest::StableMinus(n));
+    }
+    if let Ok(milestone) = value.parse::<u32>() {
+        return Some(VersionRequest::Milestone(milestone));
+    }
+    value.parse::<Version>().ok().map(VersionRequest::Fixed)
+}
+
+/// Parse a browser binary name: `chrome` or `chrome-headless-shell`.
+pub(crate) fn parse_chrome_binary(value: &str) -> Option<ChromeBinary> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "chrome" => Some(ChromeBinary::Chrome),
+        "chrome-headless-shell" | "headless-shell" => Some(ChromeBinary::ChromeHeadlessShell),
+        _ => None,
+    }
+}
+
+/// Parse a port request: a port number or `any`.
+pub(crate) fn parse_port_request(value: &str) -> Option<PortRequest> {
+    let value = value.trim();
+    if value.eq_ignore_ascii_case("any") {
+        return Some(PortRequest::Any);
+    }
+    value.parse::<u16>().ok().map(PortRequest::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Port;
+    use crate::test_support::version;
+    use assertr::prelude::*;
+    use std::collections::HashMap;
+
+    fn overrides(
+        vars: &[(&str, &str)],
+    ) -> Result<EnvOverrides, Report<ChromeForTestingManagerError>> {
+        let vars = vars
+            .iter()
+            .map(|(name, value)| ((*name).to_owned(), OsString::from(*value)))
+            .collect::<HashMap<_, _>>();
+        EnvOverrides::from_lookup(|name| vars.get(name).cloned())
+    }
+
+    #[test]
+    fn reads_all_overrides() {
+        let overrides = overrides(&[
+            (CFT_VERSION, "131.0.6778.204"),
+            (CFT_CHROME_BINARY, "chrome-headless-shell"),
+            (CFT_CACHE_DIR, "/var/cache/cft"),
+            (CFT_PORT, "9515"),
+        ])
+        .expect("valid overrides");
+
+        assert_that!(overrides).is_equal_to(EnvOverrides {
+            version: Some(VersionRequest::Fixed(version(131, 0, 6778, 204))),
+            chrome_binary: Some(ChromeBinary::ChromeHeadlessShell),
+            cache_dir: Some(PathBuf::from("/var/cache/cft")),
+            port: Some(PortRequest::Specific(Port::new(9515))),
+        });
+    }
+
+    #[test]
+    fn treats_empty_variables_as_unset() {
+        let overrides = overrides(&[(CFT_VERSION, ""), (CFT_PORT, " ")]).expect("valid overrides");
+
+        assert_that!(overrides).is_equal_to(EnvOverrides::default());
+    }
+
+    #[test]
+    fn channel_selects_latest_release_in_channel() {
+        let overrides = overrides(&[(CFT_CHANNEL, "beta")]).expect("valid overrides");
+
+        assert_that!(overrides.version).is_equal_to(Some(VersionRequest::beta()));
+    }
+
+    #[test]
+    fn parses_version_requests() {
+        assert_that!(parse_version_request("latest")).is_equal_to(Some(VersionRequest::Latest));
+        assert_that!(parse_version_request("Stable")).is_equal_to(Some(VersionRequest::stable()));
+        assert_that!(parse_version_request("stable-2"))
+            .is_equal_to(Some(VersionRequest::StableMinus(2)));
+        assert_that!(parse_version_request("system"))
+            .is_equal_to(Some(VersionRequest::SystemChrome));
+        assert_that!(parse_version_request("131"))
+            .is_equal_to(Some(VersionRequest::Milestone(131)));
+        assert_that!(parse_version_request("canary, beta,installed")).is_equal_to(Some(
+            VersionRequest::first_of([
+                VersionRequest::canary(),
+                VersionRequest::beta(),
+                VersionRequest::LatestInstalled,
+            ]),
+        ));
+        assert_that!(parse_version_request("131.0")).is_none();
+        assert_that!(parse_version_request("canary,")).is_none();
+    }
+
+    #[test]
+    fn reports_unparseable_values() {
+        for (name, value) in [
+            (CFT_VERSION, "newest"),
+            (CFT_CHANNEL, "nightly"),
+            (CFT_CHROME_BINARY, "firefox"),
+            (CFT_PORT, "70000"),
+        ] {
+            let err = overrides(&[(name, value)]).expect_err("invalid override");
+
+            assert_that!(matches!(
+                err.current_context(),
+                ChromeForTestingManagerError::InvalidEnvVar { name: n, value: v }
+                    if *n == name && v == value
+            ))
+            .is_true();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn accepts_non_unicode_cache_dirs_but_rejects_other_non_unicode_values() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let non_unicode = std::ffi::OsStr::from_bytes(b"/var/cache/\xff").to_owned();
+        let lookup = |wanted: &'static str| {
+            let non_unicode = non_unicode.clone();
+            move |name: &str| (name == wanted).then(|| non_unicode.clone())
+        };
+
+        let overrides = EnvOverrides::from_lookup(lookup(CFT_CACHE_DIR)).expect("valid overrides");
+        assert_that!(overrides.cache_dir).is_equal_to(Some(PathBuf::from(non_unicode.clone())));
+
+        let err = EnvOverrides::from_lookup(lookup(CFT_VERSION)).expect_err("invalid override");
+        assert_that!(matches!(
+            err.current_context(),
+            ChromeForTestingManagerError::InvalidEnvVar { name, .. } if *name == CFT_VERSION
+        ))
+        .is_true();
+    }
+
+    #[test]
+    fn rejects_conflicting_version_and_channel() {
+        let err = overrides(&[(CFT_VERSION, "131"), (CFT_CHANNEL, "stable")])
+            .expect_err("conflicting overrides");
+
+        assert_that!(err.to_string()).contains("conflicts with CFT_VERSION");
+    }
+}
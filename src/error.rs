@@ -22,7 +22,7 @@ use tokio::runtime::RuntimeFlavor;
 pub type Result<T> = std::result::Result<T, rootcause::Report<ChromeForTestingManagerError>>;
 
 /// The chrome-for-testing artifact involved in an operation.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum ChromeForTestingArtifact {
     /// The Chrome browser binary package.
@@ -57,10 +57,44 @@ pub enum ChromeForTestingManagerError {
         runtime_flavor: RuntimeFlavor,
     },
 
-    /// The current platform is unsupported by chrome-for-testing.
+    /// The current platform is unsupported by chrome-for-testing, and no other target platform was
+    /// selected via [`crate::ChromeForTestingManager::with_platform`].
     #[error("unsupported chrome-for-testing platform")]
     UnsupportedPlatform,
 
+    /// Binaries downloaded for another platform than the host cannot be launched.
+    #[error("cannot launch {platform} binaries on a {host_platform} host")]
+    ForeignPlatform {
+        /// The platform the manager targets.
+        platform: Platform,
+        /// The detected host platform.
+        host_platform: Platform,
+    },
+
+    /* Configuration. */
+    /// An environment variable overriding the configuration holds an unparseable value.
+    #[error("invalid value {value:?} for environment variable {name}")]
+    InvalidEnvVar {
+        /// The environment variable name.
+        name: &'static str,
+        /// The rejected value.
+        value: String,
+    },
+
+    /// The workspace config file could not be read or parsed.
+    #[error("failed to read config file {}", .path.display())]
+    ReadConfigFile {
+        /// The config file path.
+        path: PathBuf,
+    },
+
+    /// A config file setting holds an invalid value.
+    #[error("invalid value for config file setting `{key}`")]
+    InvalidConfigValue {
+        /// The setting name, or `<file>` if the file is not valid TOML or has unknown settings.
+        key: &'static str,
+    },
+
     // Cache and version resolution.
     /// The cache directory could not be determined.
     #[error("failed to determine cache directory; is $HOME set?")]
@@ -87,6 +121,35 @@ pub enum ChromeForTestingManagerError {
         cache_dir: PathBuf,
     },
 
+    /// The Cargo workspace root could not be determined.
+    #[error("failed to determine the Cargo workspace root starting from {start:?}")]
+    DetermineWorkspaceRoot {
+        /// The directory the search started from, if any could be determined.
+        start: Option<PathBuf>,
+    },
+
+    /// The version lockfile could not be read or parsed.
+    #[error("failed to read version lockfile {}", .path.display())]
+    ReadLockfile {
+        /// The lockfile path.
+        path: PathBuf,
+    },
+
+    /// The version lockfile could not be written.
+    #[error("failed to write version lockfile {}", .path.display())]
+    WriteLockfile {
+        /// The lockfile path.
+        path: PathBuf,
+    },
+
+    /// The version request cannot be pinned in a lockfile, as it uses a
+    /// [`crate::VersionFilter::matching`] predicate, which has no stable representation.
+    #[error("version request {version_request:?} cannot be pinned in a lockfile")]
+    UnpinnableVersionRequest {
+        /// The requested version selection.
+        version_request: VersionRequest,
+    },
+
     /// The known-good version manifest could not be requested.
     #[error("failed to request versions for {version_request:?}")]
     RequestVersions {
@@ -94,6 +157,29 @@ pub enum ChromeForTestingManagerError {
         version_request: VersionRequest,
     },
 
+    /// A release-index document could not be fetched.
+    #[error("failed to fetch release index from {location}")]
+    FetchReleaseIndex {
+        /// The document URL, or where a custom [`crate::ReleaseIndexSource`] loads it from, e.g. a
+        /// file path.
+        location: String,
+    },
+
+    /// A release-index document could not be deserialized.
+    #[error("failed to parse release index from {location}")]
+    ParseReleaseIndex {
+        /// The document URL, or where a custom [`crate::ReleaseIndexSource`] loads it from, e.g. a
+        /// file path.
+        location: String,
+    },
+
+    /// A fetched release-index document could not be written into the cache directory.
+    #[error("failed to write release index cache {}", .path.display())]
+    WriteReleaseIndexCache {
+        /// The cache file or directory path.
+        path: PathBuf,
+    },
+
     /// No known-good version matched the requested selection.
     #[error("could not determine a version for {version_request:?}")]
     NoMatchingVersion {
@@ -101,6 +187,47 @@ pub enum ChromeForTestingManagerError {
         version_request: VersionRequest,
     },
 
+    /// Offline mode found no installed version matching the requested selection.
+    #[error(
+        "no installed version in {} matches {version_request:?} (offline mode)",
+        .cache_dir.display()
+    )]
+    NoInstalledVersion {
+        /// The requested version selection.
+        version_request: VersionRequest,
+        /// The cache directory that was searched.
+        cache_dir: PathBuf,
+    },
+
+    /// No Chrome or Chromium installation was found on the system.
+    #[error("no system Chrome or Chromium installation found")]
+    NoSystemChrome,
+
+    /// The version of a system Chrome installation could not be determined.
+    #[error("failed to read the version of system Chrome {}", .executable.display())]
+    ReadSystemChromeVersion {
+        /// The executable that was run with `--version`.
+        executable: PathBuf,
+    },
+
+    /// The installed versions in the cache directory could not be listed.
+    #[error("failed to list installed versions in {}", .cache_dir.display())]
+    ListInstalledVersions {
+        /// The cache directory path.
+        cache_dir: PathBuf,
+    },
+
+    /// The `ChromeDriver` and browser versions to download are too far apart to work together.
+    #[error(
+        "chromedriver {chromedriver_version} is incompatible with browser {browser_version}; their majors differ by more than one"
+    )]
+    IncompatibleChromedriver {
+        /// The requested `ChromeDriver` version.
+        chromedriver_version: Version,
+        /// The requested browser version.
+        browser_version: Version,
+    },
+
     /// No Chrome binary was requested for a download operation.
     #[error("at least one Chrome binary must be requested")]
     EmptyChromeBinaryDownloadRequest,
@@ -110,7 +237,7 @@ pub enum ChromeForTestingManagerError {
     NoChromeDownload {
         /// The selected Chrome version.
         version: Version,
-        /// The detected platform.
+        /// The target platform.
         platform: Platform,
     },
 
@@ -119,7 +246,7 @@ pub enum ChromeForTestingManagerError {
     NoChromeHeadlessShellDownload {
         /// The selected Chrome version.
         version: Version,
-        /// The detected platform.
+        /// The target platform.
         platform: Platform,
     },
 
@@ -128,7 +255,7 @@ pub enum ChromeForTestingManagerError {
     NoChromedriverDownload {
         /// The selected Chrome version.
         version: Version,
-        /// The detected platform.
+        /// The target platform.
         platform: Platform,
     },
 
@@ -140,6 +267,15 @@ pub enum ChromeForTestingManagerError {
     },
 
     /* Downloads and archives. */
+    /// An artifact is not installed and offline mode forbids downloading it.
+    #[error("{artifact} {version} is not installed and cannot be downloaded in offline mode")]
+    DownloadInOfflineMode {
+        /// The artifact that would have been downloaded.
+        artifact: ChromeForTestingArtifact,
+        /// The selected Chrome version.
+        version: Version,
+    },
+
     /// The download request failed or returned a non-success status.
     #[error("failed to download {artifact} from {url}")]
     Download {
@@ -233,6 +369,66 @@ pub enum ChromeForTestingManagerError {
         path: PathBuf,
     },
 
+    /// An artifact could not be installed. Callers that joined the installation started by a
+    /// concurrent call in the same process receive this error, caused by its failure.
+    #[error("failed to install {artifact} {version} into {}", .platform_dir.display())]
+    InstallArtifact {
+        /// The artifact being installed.
+        artifact: ChromeForTestingArtifact,
+        /// The version being installed.
+        version: Version,
+        /// The `<version>/<platform>` directory installed into.
+        platform_dir: PathBuf,
+    },
+
+    /// The install manifest of an extracted artifact could not be written.
+    #[error("failed to write install manifest {}", .path.display())]
+    WriteInstallManifest {
+        /// The manifest path.
+        path: PathBuf,
+    },
+
+    /// The staging directory an artifact is retrieved and extracted into could not be created.
+    #[error("failed to create staging directory {}", .path.display())]
+    StageInstall {
+        /// The staging directory path.
+        path: PathBuf,
+    },
+
+    /// An extracted artifact could not be moved from its staging directory into place.
+    #[error("failed to move extracted artifact into {}", .path.display())]
+    CommitInstall {
+        /// The destination path.
+        path: PathBuf,
+    },
+
+    /// A broken installation could not be removed before reinstalling it.
+    #[error("failed to remove broken installation {}", .path.display())]
+    RemoveBrokenInstall {
+        /// The path of the removed file or directory.
+        path: PathBuf,
+    },
+
+    /// The install lock of a cache directory could not be acquired.
+    #[error("failed to acquire install lock {}", .path.display())]
+    AcquireInstallLock {
+        /// The lock file path.
+        path: PathBuf,
+    },
+
+    /// Another process held the install lock of a cache directory for longer than the configured
+    /// timeout.
+    #[error(
+        "timed out after {timeout:?} waiting for another process to release install lock {}",
+        .path.display()
+    )]
+    InstallLockTimeout {
+        /// The lock file path.
+        path: PathBuf,
+        /// How long was waited for the lock.
+        timeout: Duration,
+    },
+
     /* Chromedriver process lifecycle. */
     /// The chromedriver process could not be spawned.
     #[error("failed to spawn chromedriver process {}", .path.display())]
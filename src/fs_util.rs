@@ -0,0 +1,64 @@
+use crate::ChromeForTestingManagerError;
+use rootcause::{Report, prelude::ResultExt};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::fs;
+
+/// Write `contents` to `path` through a temporary file next to it, so that concurrent readers,
+/// e.g. other test processes, never observe a partially written file. Failures are reported with
+/// the context `context()`.
+///
+/// The temporary file is unique per call, so concurrent writers within a process never write into
+/// the same one either.
+pub(crate) async fn write_atomically(
+    path: &Path,
+    contents: impl AsRef<[u8]>,
+    context: impl Fn() -> ChromeForTestingManagerError,
+) -> Result<(), Report<ChromeForTestingManagerError>> {
+    static NEXT_TMP_ID: AtomicU64 = AtomicU64::new(0);
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(format!(
+        ".tmp-{}-{}",
+        std::process::id(),
+        NEXT_TMP_ID.fetch_add(1, Ordering::Relaxed)
+    ));
+    let tmp_path = PathBuf::from(tmp_path);
+    fs::write(&tmp_path, contents)
+        .await
+        .context_with(&context)?;
+    if let Err(err) = fs::rename(&tmp_path, path).await {
+        let _ = fs::remove_file(&tmp_path).await;
+        return Err(Report::new_sendsync(err).context(context()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_atomic_writes_never_mix_their_contents() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("index.json");
+        let bodies = (0..16_u8)
+            .map(|byte| vec![byte; 256 * 1024])
+            .collect::<Vec<_>>();
+
+        let results = futures::future::join_all(bodies.iter().map(|body| {
+            write_atomically(&path, body, || {
+                ChromeForTestingManagerError::WriteReleaseIndexCache { path: path.clone() }
+            })
+        }))
+        .await;
+
+        for result in results {
+            result?;
+        }
+        assert_that!(bodies.contains(&std::fs::read(&path)?)).is_true();
+        assert_that!(std::fs::read_dir(dir.path())?.count()).is_equal_to(1);
+        Ok(())
+    }
+}
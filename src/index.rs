@@ -0,0 +1,674 @@
+use crate::ChromeForTestingManagerError;
+use crate::fs_util;
+use crate::retry::{self, RetryPolicy};
+use chrome_for_testing::{KnownGoodVersions, VersionWithoutChannel};
+use reqwest::StatusCode;
+use reqwest::header::{ETAG, IF_NONE_MATCH};
+use rootcause::{Report, bail, prelude::ResultExt, report};
+use serde::de::{DeserializeOwned, IgnoredAny};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::fs;
+
+/// Base URL of the official chrome-for-testing JSON API.
+pub(crate) const API_BASE_URL: &str = "https://googlechromelabs.github.io";
+
+/// How long a fetched release index is reused before it is revalidated.
+pub(crate) const DEFAULT_INDEX_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Snapshot of the known-good-versions index, embedded by the `embedded-index` feature.
+///
+/// Refreshed with `just refresh-index-snapshot`. See `index-snapshot/README.md`.
+#[cfg(all(feature = "embedded-index", not(test)))]
+const EMBEDDED_KNOWN_GOOD_VERSIONS: &str =
+    include_str!("../index-snapshot/known-good-versions-with-downloads.json");
+
+/// Snapshot of the last-known-good-versions index, embedded alongside
+/// [`EMBEDDED_KNOWN_GOOD_VERSIONS`].
+#[cfg(all(feature = "embedded-index", not(test)))]
+const EMBEDDED_LAST_KNOWN_GOOD_VERSIONS: &str =
+    include_str!("../index-snapshot/last-known-good-versions-with-downloads.json");
+
+/// Fixed stand-in for [`EMBEDDED_KNOWN_GOOD_VERSIONS`] in unit tests, so their outcome does not
+/// depend on when the snapshot was last refreshed.
+#[cfg(all(feature = "embedded-index", test))]
+const EMBEDDED_KNOWN_GOOD_VERSIONS: &str =
+    include_str!("../test-data/release-index/known-good-versions-with-downloads.json");
+
+/// Fixed stand-in for [`EMBEDDED_LAST_KNOWN_GOOD_VERSIONS`] in unit tests.
+#[cfg(all(feature = "embedded-index", test))]
+const EMBEDDED_LAST_KNOWN_GOOD_VERSIONS: &str =
+    include_str!("../test-data/release-index/last-known-good-versions-with-downloads.json");
+
+/// A JSON document published by the chrome-for-testing API, as loaded through a
+/// [`crate::ReleaseIndexSource`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ReleaseIndexDocument {
+    /// `known-good-versions-with-downloads.json`, listing every released version.
+    KnownGoodVersions,
+
+    /// `last-known-good-versions-with-downloads.json`, listing the latest release of each channel.
+    LastKnownGoodVersions,
+
+    /// `latest-versions-per-milestone-with-downloads.json`, listing the newest build of each
+    /// milestone.
+    LatestVersionsPerMilestone,
+}
+
+impl ReleaseIndexDocument {
+    /// The file name the document is published under.
+    #[must_use]
+    pub const fn file_name(self) -> &'static str {
+        match self {
+            Self::KnownGoodVersions => "known-good-versions-with-downloads.json",
+            Self::LastKnownGoodVersions => "last-known-good-versions-with-downloads.json",
+            Self::LatestVersionsPerMilestone => "latest-versions-per-milestone-with-downloads.json",
+        }
+    }
+
+    pub(crate) fn url(self, base_url: &str) -> String {
+        format!(
+            "{}/chrome-for-testing/{}",
+            base_url.trim_end_matches('/'),
+            self.file_name()
+        )
+    }
+}
+
+impl Display for ReleaseIndexDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.file_name())
+    }
+}
+
+/// The `latest-versions-per-milestone-with-downloads.json` document.
+///
+/// Not modeled by the `chrome-for-testing` crate. Each entry has the same shape as a
+/// known-good-versions entry, plus a redundant `milestone` field.
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct LatestVersionsPerMilestone {
+    milestones: HashMap<String, VersionWithoutChannel>,
+}
+
+impl LatestVersionsPerMilestone {
+    /// The newest build of `milestone`, if the milestone is known.
+    pub(crate) fn milestone(&self, milestone: u32) -> Option<&VersionWithoutChannel> {
+        self.milestones.get(&milestone.to_string())
+    }
+}
+
+/// Bookkeeping stored next to each cached release-index document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedIndexMeta {
+    /// Seconds since the Unix epoch at which the document was last fetched or revalidated.
+    fetched_at: u64,
+
+    /// The `ETag` the server sent along with the document, if any.
+    etag: Option<String>,
+}
+
+impl CachedIndexMeta {
+    fn new(etag: Option<String>) -> Self {
+        Self {
+            fetched_at: unix_now(),
+            etag,
+        }
+    }
+
+    fn age(&self) -> Duration {
+        Duration::from_secs(unix_now().saturating_sub(self.fetched_at))
+    }
+}
+
+#[derive(Debug)]
+struct CachedIndex {
+    body: String,
+    meta: CachedIndexMeta,
+}
+
+/// Persistent cache of release-index documents, stored under `<cache_dir>/index/<base URL hash>`.
+///
+/// Documents are kept per base URL, so that switching mirrors never serves one host's document or
+/// `ETag` for another.
+#[derive(Debug)]
+pub(crate) struct ReleaseIndexCache {
+    dir: PathBuf,
+}
+
+impl ReleaseIndexCache {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn base_url_dir(&self, base_url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}", fnv1a(base_url.trim_end_matches('/'))))
+    }
+
+    fn body_path(&self, base_url: &str, document: ReleaseIndexDocument) -> PathBuf {
+        self.base_url_dir(base_url).join(document.file_name())
+    }
+
+    fn meta_path(&self, base_url: &str, document: ReleaseIndexDocument) -> PathBuf {
+        self.base_url_dir(base_url)
+            .join(format!("{}.meta", document.file_name()))
+    }
+
+    async fn read(&self, base_url: &str, document: ReleaseIndexDocument) -> Option<CachedIndex> {
+        let meta = fs::read(self.meta_path(base_url, document)).await.ok()?;
+        let meta = match serde_json::from_slice::<CachedIndexMeta>(&meta) {
+            Ok(meta) => meta,
+            Err(err) => {
+                tracing::warn!("Ignoring unreadable cache metadata for {document}: {err}");
+                return None;
+            }
+        };
+        let body = fs::read_to_string(self.body_path(base_url, document))
+            .await
+            .ok()?;
+        Some(CachedIndex { body, meta })
+    }
+
+    /// Store `body` and then its `meta`, so that metadata never describes a body not yet written.
+    async fn write(
+        &self,
+        base_url: &str,
+        document: ReleaseIndexDocument,
+        body: &str,
+        meta: &CachedIndexMeta,
+    ) -> Result<(), Report<ChromeForTestingManagerError>> {
+        let dir = self.base_url_dir(base_url);
+        fs::create_dir_all(&dir)
+            .await
+            .context(ChromeForTestingManagerError::WriteReleaseIndexCache { path: dir })?;
+        let body_path = self.body_path(base_url, document);
+        fs_util::write_atomically(&body_path, body, || {
+            ChromeForTestingManagerError::WriteReleaseIndexCache {
+                path: body_path.clone(),
+            }
+        })
+        .await?;
+        self.write_meta(base_url, document, meta).await
+    }
+
+    async fn write_meta(
+        &self,
+        base_url: &str,
+        document: ReleaseIndexDocument,
+        meta: &CachedIndexMeta,
+    ) -> Result<(), Report<ChromeForTestingManagerError>> {
+        let meta_path = self.meta_path(base_url, document);
+        let meta = serde_json::to_vec(meta).context(
+            ChromeForTestingManagerError::WriteReleaseIndexCache {
+                path: meta_path.clone(),
+            },
+        )?;
+        fs_util::write_atomically(&meta_path, meta, || {
+            ChromeForTestingManagerError::WriteReleaseIndexCache {
+                path: meta_path.clone(),
+            }
+        })
+        .await
+    }
+}
+
+/// The 64-bit FNV-1a hash of `value`.
+///
+/// Names cache directories, so unlike [`std::hash::DefaultHasher`] it must stay stable across Rust
+/// releases.
+fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Fetch the raw JSON of a release-index document over HTTP, going through the persistent `cache`
+/// if any.
+///
+/// A cached copy younger than `ttl` is used as-is. An older copy is revalidated with
+/// `If-None-Match`. Failed requests are retried according to `retry`. Should the server still be
+/// unreachable, e.g. due to a network error or a proxy answering `403` or `407`, the stale copy
+/// is used and a warning is logged. Without a cached copy, the snapshot embedded by the
+/// `embedded-index` feature is used as a last resort.
+pub(crate) async fn fetch_index_body(
+    client: &reqwest::Client,
+    cache: Option<&ReleaseIndexCache>,
+    ttl: Duration,
+    base_url: &str,
+    document: ReleaseIndexDocument,
+    retry: &RetryPolicy,
+) -> Result<String, Report<ChromeForTestingManagerError>> {
+    let url = document.url(base_url);
+    let cached = match cache {
+        Some(cache) => cache.read(base_url, document).await,
+        None => None,
+    };
+    // A corrupt copy is neither used nor revalidated through its `ETag`.
+    let cached = cached.filter(|cached| match check_json(&cached.body, &url) {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::warn!("Ignoring unparsable cached {document}: {err}");
+            false
+        }
+    });
+
+    if let Some(cached) = &cached
+        && cached.meta.age() < ttl
+    {
+        tracing::debug!("Using cached {document} (age {:?}).", cached.meta.age());
+        return Ok(cached.body.clone());
+    }
+
+    let etag = cached.as_ref().and_then(|cached| cached.meta.etag.clone());
+    let fetched = retry
+        .run(
+            || ChromeForTestingManagerError::FetchReleaseIndex {
+                location: url.clone(),
+            },
+            || fetch_fresh(client, &url, etag.as_deref()),
+        )
+        .await;
+    let (body, etag) = match fetched {
+        Ok(Fetched::Body { body, etag }) => (body, etag),
+        Ok(Fetched::NotModified) => {
+            let (Some(cache), Some(cached)) = (cache, cached) else {
+                bail!(ChromeForTestingManagerError::FetchReleaseIndex { location: url });
+            };
+            tracing::debug!("Cached {document} is still up to date.");
+            let meta = CachedIndexMeta::new(cached.meta.etag);
+            if let Err(err) = cache.write_meta(base_url, document, &meta).await {
+                tracing::warn!("Failed to update cache metadata for {document}: {err}");
+            }
+            return Ok(cached.body);
+        }
+        Err(err) if retry::is_unreachable(&err) => return use_stale(cached, document, err),
+        Err(err) => return Err(err),
+    };
+    check_json(&body, &url)?;
+
+    if let Some(cache) = cache
+        && let Err(err) = cache
+            .write(base_url, document, &body, &CachedIndexMeta::new(etag))
+            .await
+    {
+        tracing::warn!("Failed to cache {document}: {err}");
+    }
+
+    Ok(body)
+}
+
+/// The outcome of a single request for a release-index document.
+enum Fetched {
+    /// The cached copy with the sent `ETag` is still up to date.
+    NotModified,
+
+    /// The current document and its `ETag`.
+    Body { body: String, etag: Option<String> },
+}
+
+/// Request `url`, conditionally on `etag` if given.
+async fn fetch_fresh(
+    client: &reqwest::Client,
+    url: &str,
+    etag: Option<&str>,
+) -> Result<Fetched, Report<ChromeForTestingManagerError>> {
+    let context = || ChromeForTestingManagerError::FetchReleaseIndex {
+        location: url.to_owned(),
+    };
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let response = request.send().await.context_with(context)?;
+    if etag.is_some() && response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(Fetched::NotModified);
+    }
+    let response = response.error_for_status().context_with(context)?;
+    if !response.status().is_success() {
+        return Err(report!(context()).attach(format!("HTTP status {}", response.status())));
+    }
+    let etag = response
+        .headers()
+        .get(ETAG)
+        .and_then(|etag| etag.to_str().ok())
+        .map(ToOwned::to_owned);
+    let body = response.text().await.context_with(context)?;
+    Ok(Fetched::Body { body, etag })
+}
+
+fn use_stale(
+    cached: Option<CachedIndex>,
+    document: ReleaseIndexDocument,
+    err: Report<ChromeForTestingManagerError>,
+) -> Result<String, Report<ChromeForTestingManagerError>> {
+    let Some(cached) = cached else {
+        return use_embedded(document, err);
+    };
+    tracing::warn!(
+        "Failed to refresh {document}, falling back to cached copy from {:?} ago: {err}",
+        cached.meta.age()
+    );
+    Ok(cached.body)
+}
+
+fn use_embedded(
+    document: ReleaseIndexDocument,
+    err: Report<ChromeForTestingManagerError>,
+) -> Result<String, Report<ChromeForTestingManagerError>> {
+    let Some(snapshot) = embedded_snapshot(document) else {
+        return Err(err);
+    };
+    tracing::warn!(
+        "Failed to fetch {document} and no cached copy exists, falling back to the snapshot embedded in the crate: {err}"
+    );
+    Ok(snapshot.to_owned())
+}
+
+/// Whether `known_good` is the snapshot embedded by the `embedded-index` feature, i.e. the
+/// known-good-versions document could neither be fetched nor read from the cache.
+pub(crate) fn is_embedded_snapshot(known_good: &KnownGoodVersions) -> bool {
+    embedded_snapshot(ReleaseIndexDocument::KnownGoodVersions)
+        .and_then(|snapshot| serde_json::from_str::<KnownGoodVersions>(snapshot).ok())
+        .is_some_and(|snapshot| snapshot.timestamp == known_good.timestamp)
+}
+
+/// The snapshot of `document` embedded in the crate, if any.
+#[cfg(feature = "embedded-index")]
+fn embedded_snapshot(document: ReleaseIndexDocument) -> Option<&'static str> {
+    match document {
+        ReleaseIndexDocument::KnownGoodVersions => Some(EMBEDDED_KNOWN_GOOD_VERSIONS),
+        ReleaseIndexDocument::LastKnownGoodVersions => Some(EMBEDDED_LAST_KNOWN_GOOD_VERSIONS),
+        ReleaseIndexDocument::LatestVersionsPerMilestone => None,
+    }
+}
+
+#[cfg(not(feature = "embedded-index"))]
+fn embedded_snapshot(_document: ReleaseIndexDocument) -> Option<&'static str> {
+    None
+}
+
+/// Deserialize a release-index document loaded from `location`.
+pub(crate) fn parse_index<T: DeserializeOwned>(
+    body: &str,
+    location: &str,
+) -> Result<T, Report<ChromeForTestingManagerError>> {
+    serde_json::from_str(body).context(ChromeForTestingManagerError::ParseReleaseIndex {
+        location: location.to_owned(),
+    })
+}
+
+/// Fail unless `body` is well-formed JSON, so that garbage is neither cached nor served.
+fn check_json(body: &str, url: &str) -> Result<(), Report<ChromeForTestingManagerError>> {
+    parse_index::<IgnoredAny>(body, url).map(|_| ())
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+    use rootcause::Report;
+
+    const EMPTY_INDEX: &str = r#"{"timestamp":"2026-04-13T08:53:52.847Z","versions":[]}"#;
+    const INDEX_PATH: &str = "/chrome-for-testing/known-good-versions-with-downloads.json";
+
+    fn cache_in(dir: &tempfile::TempDir) -> ReleaseIndexCache {
+        ReleaseIndexCache::new(dir.path().join("index"))
+    }
+
+    async fn fetch(
+        cache: &ReleaseIndexCache,
+        ttl: Duration,
+        base_url: &str,
+    ) -> Result<KnownGoodVersions, Report<ChromeForTestingManagerError>> {
+        let body = fetch_index_body(
+            &reqwest::Client::new(),
+            Some(cache),
+            ttl,
+            base_url,
+            ReleaseIndexDocument::KnownGoodVersions,
+            &RetryPolicy::none(),
+        )
+        .await?;
+        parse_index(&body, base_url)
+    }
+
+    #[tokio::test]
+    async fn fresh_cache_is_reused_without_a_request() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", INDEX_PATH)
+            .with_header("etag", "\"v1\"")
+            .with_body(EMPTY_INDEX)
+            .expect(1)
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir()?;
+        let cache = cache_in(&dir);
+
+        fetch(&cache, DEFAULT_INDEX_TTL, &server.url()).await?;
+        fetch(&cache, DEFAULT_INDEX_TTL, &server.url()).await?;
+
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn expired_cache_is_revalidated_with_etag() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let initial = server
+            .mock("GET", INDEX_PATH)
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"v1\"")
+            .with_body(EMPTY_INDEX)
+            .create_async()
+            .await;
+        let revalidation = server
+            .mock("GET", INDEX_PATH)
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir()?;
+        let cache = cache_in(&dir);
+
+        fetch(&cache, Duration::ZERO, &server.url()).await?;
+        let index = fetch(&cache, Duration::ZERO, &server.url()).await?;
+
+        initial.assert_async().await;
+        revalidation.assert_async().await;
+        assert_that!(index.versions).is_empty();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn corrupt_cache_is_fetched_again_without_etag() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let full = server
+            .mock("GET", INDEX_PATH)
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"v1\"")
+            .with_body(EMPTY_INDEX)
+            .expect(2)
+            .create_async()
+            .await;
+        let revalidation = server
+            .mock("GET", INDEX_PATH)
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .expect(0)
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir()?;
+        let cache = cache_in(&dir);
+        fetch(&cache, DEFAULT_INDEX_TTL, &server.url()).await?;
+        std::fs::write(
+            cache.body_path(&server.url(), ReleaseIndexDocument::KnownGoodVersions),
+            &EMPTY_INDEX[..20],
+        )?;
+
+        let index = fetch(&cache, DEFAULT_INDEX_TTL, &server.url()).await?;
+
+        assert_that!(index.versions).is_empty();
+        full.assert_async().await;
+        revalidation.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cache_is_kept_per_base_url() -> Result<(), Report> {
+        let mut first = mockito::Server::new_async().await;
+        let mut second = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for server in [&mut first, &mut second] {
+            mocks.push(
+                server
+                    .mock("GET", INDEX_PATH)
+                    .with_header("etag", "\"v1\"")
+                    .with_body(EMPTY_INDEX)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        let dir = tempfile::tempdir()?;
+        let cache = cache_in(&dir);
+
+        fetch(&cache, DEFAULT_INDEX_TTL, &first.url()).await?;
+        fetch(&cache, DEFAULT_INDEX_TTL, &second.url()).await?;
+        fetch(&cache, DEFAULT_INDEX_TTL, &first.url()).await?;
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stale_cache_is_used_when_server_is_unavailable() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let dir = tempfile::tempdir()?;
+        let cache = cache_in(&dir);
+        let _ok = server
+            .mock("GET", INDEX_PATH)
+            .with_body(EMPTY_INDEX)
+            .expect(1)
+            .create_async()
+            .await;
+        fetch(&cache, Duration::ZERO, &server.url()).await?;
+
+        server.reset();
+        let _unavailable = server
+            .mock("GET", INDEX_PATH)
+            .with_status(503)
+            .create_async()
+            .await;
+
+        assert_that!(fetch(&cache, Duration::ZERO, &server.url()).await).is_ok();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn stale_cache_is_used_when_a_proxy_blocks_the_server() -> Result<(), Report> {
+        for status in [403, 407] {
+            let mut server = mockito::Server::new_async().await;
+            let dir = tempfile::tempdir()?;
+            let cache = cache_in(&dir);
+            let _ok = server
+                .mock("GET", INDEX_PATH)
+                .with_body(EMPTY_INDEX)
+                .expect(1)
+                .create_async()
+                .await;
+            fetch(&cache, Duration::ZERO, &server.url()).await?;
+
+            server.reset();
+            let _blocked = server
+                .mock("GET", INDEX_PATH)
+                .with_status(status)
+                .create_async()
+                .await;
+
+            assert_that!(fetch(&cache, Duration::ZERO, &server.url()).await).is_ok();
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn transient_failures_are_retried_before_falling_back() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let unavailable = server
+            .mock("GET", INDEX_PATH)
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", INDEX_PATH)
+            .with_body(EMPTY_INDEX)
+            .expect(1)
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir()?;
+
+        let body = fetch_index_body(
+            &reqwest::Client::new(),
+            Some(&cache_in(&dir)),
+            DEFAULT_INDEX_TTL,
+            &server.url(),
+            ReleaseIndexDocument::KnownGoodVersions,
+            &RetryPolicy::new().with_backoff(Duration::ZERO, Duration::ZERO),
+        )
+        .await?;
+
+        assert_that!(body).is_equal_to(EMPTY_INDEX.to_owned());
+        unavailable.assert_async().await;
+        ok.assert_async().await;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "embedded-index"))]
+    #[tokio::test]
+    async fn unavailable_server_without_cache_is_an_error() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let _unavailable = server
+            .mock("GET", INDEX_PATH)
+            .with_status(503)
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir()?;
+
+        assert_that!(fetch(&cache_in(&dir), DEFAULT_INDEX_TTL, &server.url()).await)
+            .is_err()
+            .derive(ToString::to_string)
+            .contains("failed to fetch release index");
+        Ok(())
+    }
+
+    #[cfg(feature = "embedded-index")]
+    #[tokio::test]
+    async fn embedded_snapshot_is_used_when_server_is_unavailable_without_cache()
+    -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let _unavailable = server
+            .mock("GET", INDEX_PATH)
+            .with_status(503)
+            .create_async()
+            .await;
+        let dir = tempfile::tempdir()?;
+
+        let index = fetch(&cache_in(&dir), DEFAULT_INDEX_TTL, &server.url()).await?;
+
+        assert_that!(index.versions).is_not_empty();
+        Ok(())
+    }
+}
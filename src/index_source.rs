@@ -0,0 +1,256 @@
+use crate::ChromeForTestingManagerError;
+use crate::index::{self, API_BASE_URL, ReleaseIndexDocument};
+use crate::retry::RetryPolicy;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use rootcause::{Report, prelude::ResultExt, report};
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Where a [`crate::ChromeForTestingManager`] loads the chrome-for-testing release index from.
+///
+/// By default, the manager fetches the index from the chrome-for-testing JSON API (or its
+/// [`crate::Mirror`]) and caches it in the cache directory. Pass another source to
+/// [`crate::ChromeForTestingManager::with_release_index_source`] to serve the index from a file
+/// share, or to test version selection against a fixed index.
+///
+/// The manager retries failed loads of any source according to its [`crate::RetryPolicy`], so a
+/// source should not retry on its own.
+///
+/// ```
+/// # use chrome_for_testing_manager::{
+/// #     ChromeForTestingManager, InMemoryReleaseIndexSource, ReleaseIndexDocument, VersionRequest,
+/// # };
+/// # async fn example() -> chrome_for_testing_manager::Result<()> {
+/// let source = InMemoryReleaseIndexSource::new().with_document(
+///     ReleaseIndexDocument::KnownGoodVersions,
+///     r#"{"timestamp":"2026-04-13T08:53:52.847Z","versions":[]}"#,
+/// );
+/// let mgr = ChromeForTestingManager::new()?.with_release_index_source(source);
+/// let selected = mgr.resolve_version(VersionRequest::Latest).await;
+/// assert!(selected.is_err());
+/// # Ok(())
+/// # }
+/// ```
+pub trait ReleaseIndexSource: Debug + Send + Sync {
+    /// Load the raw JSON of `document`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the document is not available from this source.
+    fn load(
+        &self,
+        document: ReleaseIndexDocument,
+    ) -> BoxFuture<'_, Result<String, Report<ChromeForTestingManagerError>>>;
+
+    /// Where `document` is loaded from, e.g. a URL or a path. Used in error messages.
+    fn location(&self, document: ReleaseIndexDocument) -> String;
+}
+
+/// Fetches the release index over HTTP, without caching it. Makes a single request per load, which
+/// the manager retries like the loads of any other source.
+///
+/// Like the manager's default source, a transient failure falls back to the snapshot embedded by
+/// the `embedded-index` feature, if enabled.
+#[derive(Debug, Clone)]
+pub struct HttpReleaseIndexSource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl Default for HttpReleaseIndexSource {
+    fn default() -> Self {
+        Self::new(API_BASE_URL)
+    }
+}
+
+impl HttpReleaseIndexSource {
+    /// A source fetching the documents from `<base_url>/chrome-for-testing/<file name>`.
+    #[must_use]
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.into(),
+        }
+    }
+
+    /// Send requests through `client`, e.g. one configured with a proxy or custom certificates.
+    #[must_use]
+    pub fn with_client(mut self, client: reqwest::Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// The base URL the documents are fetched from.
+    #[must_use]
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+}
+
+impl ReleaseIndexSource for HttpReleaseIndexSource {
+    fn load(
+        &self,
+        document: ReleaseIndexDocument,
+    ) -> BoxFuture<'_, Result<String, Report<ChromeForTestingManagerError>>> {
+        async move {
+            index::fetch_index_body(
+                &self.client,
+                None,
+                Duration::ZERO,
+                &self.base_url,
+                document,
+                &RetryPolicy::none(),
+            )
+            .await
+        }
+        .boxed()
+    }
+
+    fn location(&self, document: ReleaseIndexDocument) -> String {
+        document.url(&self.base_url)
+    }
+}
+
+/// Reads the release index from JSON files in a directory, named like the published documents,
+/// e.g. `known-good-versions-with-downloads.json`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileReleaseIndexSource {
+    dir: PathBuf,
+}
+
+impl FileReleaseIndexSource {
+    /// A source reading the documents from `dir`.
+    #[must_use]
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The directory the documents are read from.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, document: ReleaseIndexDocument) -> PathBuf {
+        self.dir.join(document.file_name())
+    }
+}
+
+impl ReleaseIndexSource for FileReleaseIndexSource {
+    fn load(
+        &self,
+        document: ReleaseIndexDocument,
+    ) -> BoxFuture<'_, Result<String, Report<ChromeForTestingManagerError>>> {
+        async move {
+            let path = self.path(document);
+            tokio::fs::read_to_string(&path).await.context(
+                ChromeForTestingManagerError::FetchReleaseIndex {
+                    location: path.display().to_string(),
+                },
+            )
+        }
+        .boxed()
+    }
+
+    fn location(&self, document: ReleaseIndexDocument) -> String {
+        self.path(document).display().to_string()
+    }
+}
+
+/// Serves release-index documents held in memory. Useful to test version selection against a
+/// fixed index.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InMemoryReleaseIndexSource {
+    documents: HashMap<ReleaseIndexDocument, String>,
+}
+
+impl InMemoryReleaseIndexSource {
+    /// A source without any documents.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serve `json` as `document`, replacing any previous contents.
+    #[must_use]
+    pub fn with_document(
+        mut self,
+        document: ReleaseIndexDocument,
+        json: impl Into<String>,
+    ) -> Self {
+        self.documents.insert(document, json.into());
+        self
+    }
+}
+
+impl ReleaseIndexSource for InMemoryReleaseIndexSource {
+    fn load(
+        &self,
+        document: ReleaseIndexDocument,
+    ) -> BoxFuture<'_, Result<String, Report<ChromeForTestingManagerError>>> {
+        let result = self.documents.get(&document).cloned().ok_or_else(|| {
+            report!(ChromeForTestingManagerError::FetchReleaseIndex {
+                location: self.location(document),
+            })
+            .attach("the document was not provided")
+        });
+        futures::future::ready(result).boxed()
+    }
+
+    fn location(&self, document: ReleaseIndexDocument) -> String {
+        format!("memory:{document}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+
+    const EMPTY_INDEX: &str = r#"{"timestamp":"2026-04-13T08:53:52.847Z","versions":[]}"#;
+
+    #[tokio::test]
+    async fn file_source_reads_documents_named_like_the_published_ones() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("known-good-versions-with-downloads.json"),
+            EMPTY_INDEX,
+        )?;
+        let source = FileReleaseIndexSource::new(dir.path());
+
+        assert_that!(source.load(ReleaseIndexDocument::KnownGoodVersions).await?)
+            .is_equal_to(EMPTY_INDEX.to_owned());
+        assert_that!(
+            source
+                .load(ReleaseIndexDocument::LastKnownGoodVersions)
+                .await
+        )
+        .is_err()
+        .derive(ToString::to_string)
+        .contains("last-known-good-versions-with-downloads.json");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn in_memory_source_serves_provided_documents_only() -> Result<(), Report> {
+        let source = InMemoryReleaseIndexSource::new()
+            .with_document(ReleaseIndexDocument::KnownGoodVersions, EMPTY_INDEX);
+
+        assert_that!(source.load(ReleaseIndexDocument::KnownGoodVersions).await?)
+            .is_equal_to(EMPTY_INDEX.to_owned());
+        let err = source
+            .load(ReleaseIndexDocument::LatestVersionsPerMilestone)
+            .await
+            .expect_err("document was not provided");
+        assert_that!(matches!(
+            err.current_context(),
+            ChromeForTestingManagerError::FetchReleaseIndex { location }
+                if location == "memory:latest-versions-per-milestone-with-downloads.json"
+        ))
+        .is_true();
+        Ok(())
+    }
+}
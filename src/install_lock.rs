@@ -0,0 +1,340 @@
+use crate::ChromeForTestingManagerError;
+use rootcause::{Report, prelude::ResultExt, report};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, LazyLock, Mutex, Weak};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Name of the lock file in a `<version>/<platform>` directory.
+const LOCK_FILE_NAME: &str = ".install.lock";
+
+/// Default for how long to wait for another process to finish installing into the same directory.
+pub(crate) const DEFAULT_INSTALL_LOCK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// A lock file not touched by its holder for this long is taken over.
+///
+/// The operating system releases the lock of a crashed process itself. This covers holders that
+/// hang, and file systems that keep the locks of crashed clients, e.g. network shares.
+const STALE_LOCK_AGE: Duration = Duration::from_secs(2 * 60);
+
+/// How often the holder touches its lock file to prove it is alive.
+#[cfg(not(test))]
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+#[cfg(test)]
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(50);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The install locks held by this process, by lock file path.
+static HELD: LazyLock<Mutex<HashMap<PathBuf, Weak<InstallLock>>>> = LazyLock::new(Mutex::default);
+
+/// An advisory, cross-process lock on a `<version>/<platform>` directory of the cache, held while
+/// installing into it.
+///
+/// The lock is shared by all holders within this process, which coordinate their installs through
+/// [`crate::shared_install`] instead. Released when the last holder drops it, or by the operating
+/// system when the holding process dies.
+#[derive(Debug)]
+pub(crate) struct InstallLock {
+    path: PathBuf,
+    /// `None` if the file system does not support file locks.
+    file: Option<File>,
+    heartbeat: Option<Heartbeat>,
+}
+
+impl InstallLock {
+    /// Lock `platform_dir`, waiting up to `timeout` for other processes to release it.
+    pub(crate) async fn acquire(
+        platform_dir: &Path,
+        timeout: Duration,
+    ) -> Result<Arc<Self>, Report<ChromeForTestingManagerError>> {
+        let path = platform_dir.join(LOCK_FILE_NAME);
+        let context = || ChromeForTestingManagerError::AcquireInstallLock { path: path.clone() };
+        let started = Instant::now();
+        let mut waiting = false;
+        loop {
+            if let Some(lock) = held_in_process(&path) {
+                return Ok(lock);
+            }
+            let file = OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .read(true)
+                .write(true)
+                .open(&path)
+                .context_with(context)?;
+            match file.try_lock() {
+                // The lock file may have been taken over between opening and locking it.
+                Ok(()) if is_current_lock_file(&file, &path) => {
+                    if waiting {
+                        tracing::info!("Acquired install lock {path:?}.");
+                    }
+                    return Ok(Self::held(path, Some(file)));
+                }
+                Ok(()) => continue,
+                Err(TryLockError::Error(err)) if err.kind() == std::io::ErrorKind::Unsupported => {
+                    tracing::warn!(
+                        "File locks are not supported for {path:?}; installing without a lock."
+                    );
+                    return Ok(Self::held(path, None));
+                }
+                Err(TryLockError::Error(err)) => {
+                    return Err(Report::new_sendsync(err).context(context()));
+                }
+                Err(TryLockError::WouldBlock) => {}
+            }
+
+            if !is_current_lock_file(&file, &path) {
+                continue;
+            }
+            if is_stale(&path) {
+                tracing::warn!(
+                    "Taking over install lock {path:?}, which was not touched by its holder for {STALE_LOCK_AGE:?}."
+                );
+                if let Err(err) = std::fs::remove_file(&path)
+                    && err.kind() != std::io::ErrorKind::NotFound
+                {
+                    return Err(Report::new_sendsync(err).context(context()));
+                }
+                continue;
+            }
+            if started.elapsed() >= timeout {
+                return Err(report!(ChromeForTestingManagerError::InstallLockTimeout {
+                    path,
+                    timeout,
+                }));
+            }
+            if !waiting {
+                tracing::info!(
+                    "Waiting for another process to finish installing into {platform_dir:?}..."
+                );
+                waiting = true;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Register the lock on `path` as held by this process.
+    fn held(path: PathBuf, file: Option<File>) -> Arc<Self> {
+        let heartbeat = file.as_ref().and_then(|file| {
+            touch(file);
+            Heartbeat::start(file.try_clone().ok()?)
+        });
+        let lock = Arc::new(Self {
+            path: path.clone(),
+            file,
+            heartbeat,
+        });
+        HELD.lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .insert(path, Arc::downgrade(&lock));
+        lock
+    }
+}
+
+/// The lock on `path` if another holder in this process currently holds it.
+fn held_in_process(path: &Path) -> Option<Arc<InstallLock>> {
+    HELD.lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(path)
+        .and_then(Weak::upgrade)
+}
+
+impl Drop for InstallLock {
+    fn drop(&mut self) {
+        let mut held = HELD
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if held
+            .get(&self.path)
+            .is_some_and(|lock| lock.strong_count() == 0)
+        {
+            held.remove(&self.path);
+        }
+        drop(held);
+
+        if let Some(heartbeat) = self.heartbeat.take() {
+            heartbeat.stop();
+        }
+        if let Some(file) = self.file.take()
+            && let Err(err) = file.unlock()
+        {
+            tracing::warn!("Failed to release install lock: {err}");
+        }
+    }
+}
+
+/// Touches a lock file every [`HEARTBEAT_INTERVAL`] until stopped.
+///
+/// Runs on a dedicated thread rather than the async runtime, so it keeps beating while the holder
+/// blocks its runtime, e.g. extracting an archive on a current-thread runtime, and outlives the
+/// runtime that acquired the lock while other holders in the process still share it.
+#[derive(Debug)]
+struct Heartbeat {
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl Heartbeat {
+    fn start(file: File) -> Option<Self> {
+        let (stop, stopped) = mpsc::channel();
+        let thread = std::thread::Builder::new()
+            .name("install-lock-heartbeat".to_owned())
+            .spawn(move || {
+                while let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(HEARTBEAT_INTERVAL)
+                {
+                    touch(&file);
+                }
+            })
+            .inspect_err(|err| tracing::warn!("Failed to start install lock heartbeat: {err}"))
+            .ok()?;
+        Some(Self { stop, thread })
+    }
+
+    fn stop(self) {
+        drop(self.stop);
+        if self.thread.join().is_err() {
+            tracing::warn!("Install lock heartbeat panicked.");
+        }
+    }
+}
+
+fn touch(file: &File) {
+    if let Err(err) = file.set_modified(SystemTime::now()) {
+        tracing::debug!("Failed to touch install lock: {err}");
+    }
+}
+
+/// Whether the lock file at `path` was not touched for [`STALE_LOCK_AGE`].
+fn is_stale(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .is_ok_and(|modified| modified.elapsed().is_ok_and(|age| age >= STALE_LOCK_AGE))
+}
+
+/// Whether `file` still is the lock file at `path`, i.e. was not taken over after being opened.
+#[cfg(unix)]
+fn is_current_lock_file(file: &File, path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (file.metadata(), std::fs::metadata(path)) {
+        (Ok(opened), Ok(current)) => opened.dev() == current.dev() && opened.ino() == current.ino(),
+        _ => false,
+    }
+}
+
+/// Whether `file` still is the lock file at `path`, i.e. was not taken over after being opened.
+///
+/// Windows does not expose file identities on stable Rust; a lock file that disappeared is the
+/// best available sign of a takeover.
+#[cfg(not(unix))]
+fn is_current_lock_file(_file: &File, path: &Path) -> bool {
+    path.exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+
+    /// Lock the lock file of `dir` like another process would.
+    fn lock_as_other_process(dir: &Path) -> Result<File, Report> {
+        let file = File::create(dir.join(LOCK_FILE_NAME))?;
+        file.lock()?;
+        Ok(file)
+    }
+
+    #[tokio::test]
+    async fn waits_for_other_processes_until_the_timeout() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let _other = lock_as_other_process(dir.path())?;
+
+        let err = InstallLock::acquire(dir.path(), Duration::from_millis(300))
+            .await
+            .expect_err("lock is held");
+
+        assert_that!(matches!(
+            err.current_context(),
+            ChromeForTestingManagerError::InstallLockTimeout { .. }
+        ))
+        .is_true();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn is_acquired_once_other_processes_release_it() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let other = lock_as_other_process(dir.path())?;
+
+        let waiter = tokio::spawn({
+            let dir = dir.path().to_owned();
+            async move { InstallLock::acquire(&dir, Duration::from_secs(10)).await }
+        });
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_that!(waiter.is_finished()).is_false();
+        other.unlock()?;
+
+        assert_that!(waiter.await?.is_ok()).is_true();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn is_shared_within_the_process() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let first = InstallLock::acquire(dir.path(), Duration::ZERO).await?;
+        let second = InstallLock::acquire(dir.path(), Duration::ZERO).await?;
+        assert_that!(Arc::ptr_eq(&first, &second)).is_true();
+
+        drop((first, second));
+        let other = File::open(dir.path().join(LOCK_FILE_NAME))?;
+        assert_that!(other.try_lock().is_ok()).is_true();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn takes_over_stale_locks() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        // A holder that stopped touching its lock file, e.g. a hung process.
+        let stale = lock_as_other_process(dir.path())?;
+        stale.set_modified(SystemTime::now() - 2 * STALE_LOCK_AGE)?;
+
+        let lock = InstallLock::acquire(dir.path(), Duration::from_secs(10)).await?;
+
+        assert_that!(lock.file.is_some()).is_true();
+        Ok(())
+    }
+
+    #[test]
+    fn busy_holders_are_not_taken_over() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join(LOCK_FILE_NAME);
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let lock = runtime.block_on(InstallLock::acquire(dir.path(), Duration::ZERO))?;
+        let untouched = SystemTime::now() - 2 * STALE_LOCK_AGE;
+
+        // Block like a synchronous extraction, without ever yielding to the runtime.
+        File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(untouched)?;
+        std::thread::sleep(4 * HEARTBEAT_INTERVAL);
+        assert_that!(is_stale(&path)).is_false();
+
+        // The lock outlives the runtime that acquired it.
+        drop(runtime);
+        File::options()
+            .write(true)
+            .open(&path)?
+            .set_modified(untouched)?;
+        std::thread::sleep(4 * HEARTBEAT_INTERVAL);
+        assert_that!(is_stale(&path)).is_false();
+
+        drop(lock);
+        Ok(())
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{ChromeForTestingArtifact, ChromeForTestingManagerError, fs_util};
+use rootcause::{Report, prelude::ResultExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Bookkeeping written next to an extracted artifact once its extraction completed.
+///
+/// An artifact only counts as installed while its manifest exists, so interrupted extractions are
+/// detected and redone. [`crate::ChromeForTestingManager::verify`] re-checks the recorded files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct InstallManifest {
+    /// The upstream download URL of the archive.
+    url: String,
+
+    /// Where the archive was actually retrieved from, e.g. a mirror URL.
+    source: String,
+
+    /// Seconds since the Unix epoch at which the artifact was installed.
+    installed_at: u64,
+
+    /// The extracted regular files, keyed by their `/`-separated path relative to the platform
+    /// directory.
+    files: BTreeMap<String, InstalledFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct InstalledFile {
+    size: u64,
+    sha256: String,
+}
+
+impl InstallManifest {
+    /// The path of the manifest of `artifact` in `platform_dir`.
+    pub(crate) fn path(platform_dir: &Path, artifact: ChromeForTestingArtifact) -> PathBuf {
+        platform_dir.join(format!("{artifact}.install.json"))
+    }
+
+    /// Record the size and SHA-256 of each of `files`, relative to `platform_dir`.
+    ///
+    /// The files are hashed on the blocking thread pool.
+    pub(crate) async fn create(
+        platform_dir: PathBuf,
+        files: Vec<PathBuf>,
+        url: String,
+        source: String,
+    ) -> Result<Self, std::io::Error> {
+        let files = spawn_blocking(move || {
+            files
+                .iter()
+                .map(|file| {
+                    let (size, sha256) = hash_file(&platform_dir.join(file))?;
+                    Ok((manifest_key(file), InstalledFile { size, sha256 }))
+                })
+                .collect::<Result<_, std::io::Error>>()
+        })
+        .await?;
+        Ok(Self {
+            url,
+            source,
+            installed_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            files,
+        })
+    }
+
+    /// Read the manifest at `path`. `None` if it is missing or unreadable.
+    pub(crate) fn read(path: &Path) -> Option<Self> {
+        let contents = std::fs::read(path).ok()?;
+        match serde_json::from_slice(&contents) {
+            Ok(manifest) => Some(manifest),
+            Err(err) => {
+                tracing::warn!("Ignoring unreadable install manifest {path:?}: {err}");
+                None
+            }
+        }
+    }
+
+    /// Write the manifest to `path`, atomically replacing any previous one, so that a manifest is
+    /// never observed half-written.
+    pub(crate) async fn write(
+        &self,
+        path: &Path,
+    ) -> Result<(), Report<ChromeForTestingManagerError>> {
+        let context = || ChromeForTestingManagerError::WriteInstallManifest {
+            path: path.to_owned(),
+        };
+        let contents = serde_json::to_vec_pretty(self).context_with(context)?;
+        fs_util::write_atomically(path, contents, context).await
+    }
+
+    /// The upstream download URL of the archive.
+    pub(crate) fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Re-check the recorded files in `platform_dir`, on the blocking thread pool.
+    pub(crate) async fn verify(&self, platform_dir: &Path) -> Vec<IntegrityProblem> {
+        let manifest = self.clone();
+        let platform_dir = platform_dir.to_owned();
+        spawn_blocking(move || manifest.verify_blocking(&platform_dir)).await
+    }
+
+    fn verify_blocking(&self, platform_dir: &Path) -> Vec<IntegrityProblem> {
+        let mut problems = Vec::new();
+        for (key, recorded) in &self.files {
+            let path = PathBuf::from(key);
+            let full_path = platform_dir.join(&path);
+            let actual_size = match std::fs::metadata(&full_path) {
+                Ok(metadata) if metadata.is_file() => metadata.len(),
+                Ok(_) | Err(_) => {
+                    problems.push(IntegrityProblem::MissingFile { path });
+                    continue;
+                }
+            };
+            if actual_size != recorded.size {
+                problems.push(IntegrityProblem::SizeMismatch {
+                    path,
+                    expected: recorded.size,
+                    actual: actual_size,
+                });
+                continue;
+            }
+            match hash_file(&full_path) {
+                Ok((_, sha256)) if sha256 == recorded.sha256 => {}
+                Ok(_) => problems.push(IntegrityProblem::ChecksumMismatch { path }),
+                Err(_) => problems.push(IntegrityProblem::MissingFile { path }),
+            }
+        }
+        problems
+    }
+}
+
+/// A defect found by [`crate::ChromeForTestingManager::verify`] in an installed artifact.
+///
+/// Paths are relative to the `<version>/<platform>` directory of the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum IntegrityProblem {
+    /// The artifact has no readable install manifest, e.g. because its extraction was interrupted
+    /// or it was installed by an older version of this crate.
+    MissingManifest,
+
+    /// A recorded file is missing or cannot be read.
+    MissingFile {
+        /// The file path.
+        path: PathBuf,
+    },
+
+    /// A recorded file has a different size than when it was installed.
+    SizeMismatch {
+        /// The file path.
+        path: PathBuf,
+
+        /// The recorded size in bytes.
+        expected: u64,
+
+        /// The current size in bytes.
+        actual: u64,
+    },
+
+    /// A recorded file has a different SHA-256 checksum than when it was installed.
+    ChecksumMismatch {
+        /// The file path.
+        path: PathBuf,
+    },
+}
+
+/// The result of verifying one installed artifact with [`crate::ChromeForTestingManager::verify`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArtifactVerification {
+    pub(crate) artifact: ChromeForTestingArtifact,
+    pub(crate) problems: Vec<IntegrityProblem>,
+}
+
+impl ArtifactVerification {
+    /// The verified artifact.
+    #[must_use]
+    pub const fn artifact(&self) -> ChromeForTestingArtifact {
+        self.artifact
+    }
+
+    /// The problems found. The artifact was reinstalled if there are any.
+    #[must_use]
+    pub fn problems(&self) -> &[IntegrityProblem] {
+        &self.problems
+    }
+
+    /// Whether the artifact was found intact, i.e. without reinstalling it.
+    #[must_use]
+    pub fn is_intact(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// The manifest key of `path`: its components joined by `/`, independent of the host platform.
+fn manifest_key(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Run `f` on tokio's blocking thread pool, so that hashing hundreds of megabytes does not stall
+/// the async runtime. A panic of `f` is propagated.
+async fn spawn_blocking<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    match tokio::task::spawn_blocking(f).await {
+        Ok(value) => value,
+        Err(err) => std::panic::resume_unwind(err.into_panic()),
+    }
+}
+
+/// The size and hex-encoded SHA-256 checksum of the file at `path`.
+fn hash_file(path: &Path) -> Result<(u64, String), std::io::Error> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 64 * 1024];
+    let mut size = 0;
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    let mut sha256 = String::with_capacity(64);
+    for byte in hasher.finalize() {
+        let _ = write!(sha256, "{byte:02x}");
+    }
+    Ok((size, sha256))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+
+    #[test]
+    fn hashes_files_with_sha256() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("file");
+        std::fs::write(&path, b"abc")?;
+
+        assert_that!(hash_file(&path)?).is_equal_to((
+            3,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_owned(),
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn detects_missing_resized_and_modified_files() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("chromedriver-linux64"))?;
+        let files = ["a", "b", "c", "d"]
+            .map(|name| PathBuf::from("chromedriver-linux64").join(name))
+            .to_vec();
+        for file in &files {
+            std::fs::write(dir.path().join(file), b"abc")?;
+        }
+        let manifest = InstallManifest::create(
+            dir.path().to_owned(),
+            files.clone(),
+            "url".to_owned(),
+            "source".to_owned(),
+        )
+        .await?;
+        assert_that!(manifest.verify(dir.path()).await).is_empty();
+
+        std::fs::remove_file(dir.path().join(&files[1]))?;
+        std::fs::write(dir.path().join(&files[2]), b"abcd")?;
+        std::fs::write(dir.path().join(&files[3]), b"xyz")?;
+
+        assert_that!(manifest.verify(dir.path()).await).is_equal_to(vec![
+            IntegrityProblem::MissingFile {
+                path: PathBuf::from("chromedriver-linux64/b"),
+            },
+            IntegrityProblem::SizeMismatch {
+                path: PathBuf::from("chromedriver-linux64/c"),
+                expected: 3,
+                actual: 4,
+            },
+            IntegrityProblem::ChecksumMismatch {
+                path: PathBuf::from("chromedriver-linux64/d"),
+            },
+        ]);
+        Ok(())
+    }
+}
@@ -12,37 +12,71 @@
 
 #![allow(clippy::non_minimal_cfg)] // Keep provider feature lists easy to extend.
 
+mod artifact_source;
 mod cache;
 pub(crate) mod chromedriver;
+mod config_file;
 mod download;
+mod env;
 mod error;
+mod fs_util;
+mod index;
+mod index_source;
+mod install_lock;
+mod install_manifest;
+mod lockfile;
 pub(crate) mod mgr;
+mod mirror;
 mod output;
 pub(crate) mod port;
+mod retry;
 #[cfg(any(feature = "thirtyfour"))]
 pub(crate) mod session;
 #[cfg(any(feature = "thirtyfour"))]
 pub(crate) mod session_builder;
+mod shared_install;
+mod staging;
+mod staleness;
+mod system_chrome;
+#[cfg(test)]
+mod test_support;
 pub(crate) mod version;
+mod workspace;
 
+pub use artifact_source::{
+    ArtifactRequest, ArtifactSource, DirectoryArtifactSource, HttpArtifactSource,
+    ReaderArtifactSource,
+};
 pub use chrome_for_testing::Channel;
+pub use chrome_for_testing::Download;
+pub use chrome_for_testing::Platform;
 pub use chrome_for_testing::Version;
 pub use chromedriver::{Chromedriver, ChromedriverRunConfig};
 pub use error::{ChromeForTestingArtifact, ChromeForTestingManagerError, Result};
+pub use index::ReleaseIndexDocument;
+pub use index_source::{
+    FileReleaseIndexSource, HttpReleaseIndexSource, InMemoryReleaseIndexSource, ReleaseIndexSource,
+};
+pub use install_manifest::{ArtifactVerification, IntegrityProblem};
+pub use lockfile::Lockfile;
 pub use mgr::{
     ChromeBinary, ChromeForTestingManager, LoadedBrowserPackage, LoadedChromeHeadlessShellPackage,
     LoadedChromePackage,
 };
+pub use mirror::Mirror;
 pub use output::{
     DriverOutputInspectors, DriverOutputLine, DriverOutputListener, DriverOutputSource,
 };
 pub use port::{Port, PortRequest};
+pub use retry::{RetryPolicy, RetryableStatus};
 #[cfg(any(feature = "thirtyfour"))]
 pub use session::Session;
 #[cfg(any(feature = "thirtyfour"))]
 pub use session_builder::SessionBuilder;
+pub use staleness::{StalenessAdvisory, StalenessCheck};
+pub use system_chrome::SystemChrome;
 pub use tokio_process_tools::{
     GracefulShutdown, GracefulShutdownBuilder, UnixGracefulPhase, UnixGracefulShutdown,
     UnixGracefulSignal, WindowsGracefulShutdown,
 };
-pub use version::{SelectedVersion, VersionRequest};
+pub use version::{AvailableVersion, SelectedVersion, VersionFilter, VersionRequest};
@@ -0,0 +1,367 @@
+use crate::version::{ArtifactDownloads, SelectedVersion, VersionRequest};
+use crate::{ChromeForTestingManagerError, fs_util, workspace};
+use chrome_for_testing::{Channel, Download, Platform, Version};
+use rootcause::{Report, prelude::ResultExt};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+
+/// File name of the lockfile created by [`Lockfile::at_workspace_root`].
+pub(crate) const LOCKFILE_NAME: &str = "chrome-for-testing.lock";
+
+const LOCKFILE_HEADER: &str = "\
+# This file is generated by chrome-for-testing-manager.
+# It pins the Chrome for Testing version resolved for the configured version request.
+# Commit it to share the pinned version with your team and CI.
+# Enable lockfile updates or delete this file to resolve the request again.
+";
+
+/// Opt-in lockfile pinning the resolved version across runs and machines.
+///
+/// The first resolution of a [`VersionRequest`] writes the selected version and its download URLs
+/// into the lockfile. Later resolutions of the same request reuse the pinned version, even after
+/// the release index moved on, until the lockfile is updated explicitly via
+/// [`Self::with_update`] or deleted. A different request than the one recorded replaces the pin.
+///
+/// Download URLs are recorded per platform. A platform not yet recorded resolves the pinned
+/// version and adds its URLs to the lockfile.
+///
+/// Requests using a [`crate::VersionFilter::matching`] predicate cannot be pinned, as predicates
+/// cannot be told apart once written to disk.
+///
+/// ```no_run
+/// # use chrome_for_testing_manager::{ChromeForTestingManager, Lockfile, VersionRequest};
+/// # async fn example() -> chrome_for_testing_manager::Result<()> {
+/// let update = std::env::var_os("UPDATE_CHROME").is_some();
+/// let mgr = ChromeForTestingManager::new()?
+///     .with_lockfile(Lockfile::at_workspace_root()?.with_update(update));
+/// let selected = mgr.resolve_version(VersionRequest::stable()).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lockfile {
+    path: PathBuf,
+    update: bool,
+}
+
+impl Lockfile {
+    /// A lockfile stored at `path`.
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            update: false,
+        }
+    }
+
+    /// A `chrome-for-testing.lock` lockfile in the root directory of the current Cargo workspace.
+    ///
+    /// The workspace is discovered starting from `$CARGO_MANIFEST_DIR`, falling back to the
+    /// current working directory.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no `Cargo.toml` is found in the start directory or any of its parents.
+    pub fn at_workspace_root() -> Result<Self, Report<ChromeForTestingManagerError>> {
+        Ok(Self::new(workspace::workspace_root()?.join(LOCKFILE_NAME)))
+    }
+
+    /// Ignore the pinned version and record a freshly resolved one instead.
+    #[must_use]
+    pub fn with_update(mut self, update: bool) -> Self {
+        self.update = update;
+        self
+    }
+
+    /// The path of the lockfile.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the pinned version is replaced on the next resolution.
+    #[must_use]
+    pub const fn is_update(&self) -> bool {
+        self.update
+    }
+
+    /// Read the pinned version. `None` if the lockfile does not exist yet.
+    pub(crate) async fn read(
+        &self,
+    ) -> Result<Option<LockedVersion>, Report<ChromeForTestingManagerError>> {
+        let contents = match fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => {
+                return Err(Report::new_sendsync(err).context(
+                    ChromeForTestingManagerError::ReadLockfile {
+                        path: self.path.clone(),
+                    },
+                ));
+            }
+        };
+        let locked =
+            toml::from_str(&contents).context(ChromeForTestingManagerError::ReadLockfile {
+                path: self.path.clone(),
+            })?;
+        Ok(Some(locked))
+    }
+
+    /// Write `locked`, atomically replacing the previous lockfile, so that concurrently running
+    /// test processes never read a half-written one.
+    pub(crate) async fn write(
+        &self,
+        locked: &LockedVersion,
+    ) -> Result<(), Report<ChromeForTestingManagerError>> {
+        let context = || ChromeForTestingManagerError::WriteLockfile {
+            path: self.path.clone(),
+        };
+        let contents = toml::to_string(locked).context_with(context)?;
+        fs_util::write_atomically(
+            &self.path,
+            format!("{LOCKFILE_HEADER}\n{contents}"),
+            context,
+        )
+        .await
+    }
+}
+
+/// The contents of a [`Lockfile`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct LockedVersion {
+    /// The [`request_key`] of the [`VersionRequest`] the version was resolved for.
+    request: String,
+    version: Version,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    channel: Option<Channel>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    milestone: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    revision: Option<String>,
+    /// Download URLs, keyed by platform.
+    #[serde(default)]
+    downloads: BTreeMap<String, LockedDownloads>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct LockedDownloads {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chrome: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chrome_headless_shell: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    chromedriver: Option<String>,
+}
+
+impl LockedVersion {
+    /// Pin `selected` for the request with the [`request_key`] `request`.
+    pub(crate) fn new(request: String, selected: &SelectedVersion, platform: Platform) -> Self {
+        let mut locked = Self {
+            request,
+            version: selected.version,
+            channel: selected.channel.clone(),
+            milestone: selected.milestone,
+            revision: selected.revision.clone(),
+            downloads: BTreeMap::new(),
+        };
+        locked.record(selected, platform);
+        locked
+    }
+
+    /// Whether this version was pinned for the request with the [`request_key`] `request`.
+    pub(crate) fn is_for(&self, request: &str) -> bool {
+        self.request == request
+    }
+
+    pub(crate) const fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Record the download URLs of `selected` for `platform`.
+    pub(crate) fn record(&mut self, selected: &SelectedVersion, platform: Platform) {
+        let url = |download: &Option<Download>| download.as_ref().map(|d| d.url.clone());
+        self.downloads.insert(
+            platform.to_string(),
+            LockedDownloads {
+                chrome: url(&selected.chrome),
+                chrome_headless_shell: url(&selected.chrome_headless_shell),
+                chromedriver: url(&selected.chromedriver),
+            },
+        );
+    }
+
+    /// The pinned version with the download URLs recorded for `platform`, if any.
+    ///
+    /// [`SelectedVersion::downloads`] lists the URLs of all recorded platforms.
+    pub(crate) fn selected_version(&self, platform: Platform) -> Option<SelectedVersion> {
+        let downloads = self.downloads.get(&platform.to_string())?;
+        let download = |url: &Option<String>| url.clone().map(|url| Download { platform, url });
+
+        let mut all_downloads = ArtifactDownloads::default();
+        for (recorded_platform, recorded) in &self.downloads {
+            let Ok(recorded_platform) = recorded_platform.parse::<Platform>() else {
+                continue;
+            };
+            let download = |url: &Option<String>| {
+                url.clone().map(|url| Download {
+                    platform: recorded_platform,
+                    url,
+                })
+            };
+            all_downloads.chrome.extend(download(&recorded.chrome));
+            all_downloads
+                .chrome_headless_shell
+                .extend(download(&recorded.chrome_headless_shell));
+            all_downloads
+                .chromedriver
+                .extend(download(&recorded.chromedriver));
+        }
+
+        Some(SelectedVersion {
+            channel: self.channel.clone(),
+            version: self.version,
+            milestone: self.milestone,
+            revision: self.revision.clone(),
+            chrome: download(&downloads.chrome),
+            chrome_headless_shell: download(&downloads.chrome_headless_shell),
+            chromedriver: download(&downloads.chromedriver),
+            downloads: all_downloads,
+            head_of_channels: None,
+            system_chrome: None,
+            staleness: None,
+        })
+    }
+}
+
+/// The stable representation `request` is recorded under, e.g. `latest-in Stable` or
+/// `first-of(latest-in Canary | milestone 131)`.
+///
+/// `None` if `request` uses a [`crate::VersionFilter::matching`] predicate, which cannot be
+/// represented.
+pub(crate) fn request_key(request: &VersionRequest) -> Option<String> {
+    Some(match request {
+        VersionRequest::Latest => String::from("latest"),
+        VersionRequest::LatestIn(channel) => format!("latest-in {channel}"),
+        VersionRequest::Fixed(version) => format!("fixed {version}"),
+        VersionRequest::Milestone(milestone) => format!("milestone {milestone}"),
+        VersionRequest::StableMinus(n) => format!("stable-minus {n}"),
+        VersionRequest::SystemChrome => String::from("system-chrome"),
+        VersionRequest::Matching(filter) => format!("matching({})", filter.key()?),
+        VersionRequest::LatestInstalled => String::from("latest-installed"),
+        VersionRequest::FirstOf(requests) => format!(
+            "first-of({})",
+            requests
+                .iter()
+                .map(request_key)
+                .collect::<Option<Vec<_>>>()?
+                .join(" | ")
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::version;
+    use crate::version::VersionFilter;
+    use crate::version::canonical_download;
+    use crate::{ChromeBinary, ChromeForTestingArtifact};
+    use assertr::prelude::*;
+
+    fn selected(version: Version, platform: Platform) -> SelectedVersion {
+        SelectedVersion {
+            channel: Some(Channel::Stable),
+            version,
+            milestone: Some(135),
+            revision: Some("1368529".to_owned()),
+            chrome: Some(canonical_download(
+                ChromeForTestingArtifact::Chrome,
+                version,
+                platform,
+            )),
+            chrome_headless_shell: None,
+            chromedriver: Some(canonical_download(
+                ChromeForTestingArtifact::ChromeDriver,
+                version,
+                platform,
+            )),
+            downloads: ArtifactDownloads::default(),
+            head_of_channels: None,
+            system_chrome: None,
+            staleness: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn round_trips_the_pinned_version_per_platform() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let lockfile = Lockfile::new(dir.path().join(LOCKFILE_NAME));
+        let v = version(135, 0, 7019, 0);
+        let mut locked = LockedVersion::new(
+            request_key(&VersionRequest::stable()).expect("representable"),
+            &selected(v, Platform::Linux64),
+            Platform::Linux64,
+        );
+        locked.record(&selected(v, Platform::MacArm64), Platform::MacArm64);
+
+        assert_that!(lockfile.read().await?).is_none();
+        lockfile.write(&locked).await?;
+        let read = lockfile.read().await?.expect("lockfile was written");
+
+        assert_that!(read.clone()).is_equal_to(locked);
+        assert_that!(read.is_for("latest-in Stable")).is_true();
+        assert_that!(read.is_for("latest-in Beta")).is_false();
+        let selected = read
+            .selected_version(Platform::MacArm64)
+            .expect("recorded platform");
+        assert_that!(selected.version()).is_equal_to(v);
+        assert_that!(selected.channel()).is_equal_to(Some(&Channel::Stable));
+        assert_that!(selected.has_chromedriver_download()).is_true();
+        assert_that!(selected.has_chrome_headless_shell_download()).is_false();
+        assert_that!(selected.milestone()).is_equal_to(Some(135));
+        assert_that!(selected.revision()).is_equal_to(Some("1368529"));
+        assert_that!(
+            selected
+                .downloads(ChromeForTestingArtifact::ChromeDriver)
+                .len()
+        )
+        .is_equal_to(2);
+        assert_that!(read.selected_version(Platform::Win64)).is_none();
+        Ok(())
+    }
+
+    #[test]
+    fn request_keys_are_stable_and_distinguish_filters() {
+        let from = version(120, 0, 6099, 0);
+        let to = version(125, 0, 0, 0);
+
+        assert_that!(request_key(&VersionRequest::first_of([
+            VersionRequest::canary(),
+            VersionRequest::Milestone(131),
+            VersionRequest::LatestInstalled,
+        ])))
+        .is_equal_to(Some(String::from(
+            "first-of(latest-in Canary | milestone 131 | latest-installed)",
+        )));
+        assert_that!(request_key(&VersionRequest::from(
+            VersionFilter::new()
+                .range(from..to)
+                .requires(ChromeBinary::ChromeHeadlessShell)
+        )))
+        .is_equal_to(Some(String::from(
+            "matching(>= 120.0.6099.0, < 125.0.0.0, requires chrome-headless-shell)",
+        )));
+        assert_that!(request_key(&VersionRequest::from(
+            VersionFilter::new().range(from..)
+        )))
+        .is_equal_to(Some(String::from("matching(>= 120.0.6099.0)")));
+        assert_that!(request_key(&VersionRequest::first_of([
+            VersionRequest::stable(),
+            VersionRequest::from(VersionFilter::new().matching(|_| true)),
+        ])))
+        .is_none();
+    }
+}
@@ -1,10 +1,29 @@
+use crate::artifact_source::{ArtifactRequest, ArtifactSource, HttpArtifactSource};
 use crate::cache::CacheDir;
 use crate::download;
+use crate::index::{
+    self, DEFAULT_INDEX_TTL, LatestVersionsPerMilestone, ReleaseIndexCache, ReleaseIndexDocument,
+};
+use crate::index_source::ReleaseIndexSource;
+use crate::install_lock::{DEFAULT_INSTALL_LOCK_TIMEOUT, InstallLock};
+use crate::install_manifest::{ArtifactVerification, InstallManifest, IntegrityProblem};
+use crate::lockfile::{self, LockedVersion, Lockfile};
+use crate::mirror::Mirror;
 use crate::output::{DriverOutputInspectors, DriverOutputListener};
 use crate::port::{Port, PortRequest};
-use crate::version::{SelectedVersion, VersionRequest};
+use crate::retry::{self, RetryPolicy};
+use crate::shared_install;
+use crate::staging::{self, StagingDir};
+use crate::staleness::StalenessCheck;
+use crate::system_chrome::{SystemChrome, closest_version};
+use crate::version::{
+    ArtifactDownloads, AvailableVersion, SelectedVersion, VersionExclusion, VersionFilter,
+    VersionRequest, available_versions, canonical_download, channel_heads,
+};
 use crate::{ChromeForTestingArtifact, ChromeForTestingManagerError};
 use chrome_for_testing::{KnownGoodVersions, LastKnownGoodVersions, Platform, Version};
+use futures::FutureExt;
+use rootcause::report_collection::ReportCollection;
 use rootcause::{Report, bail, option_ext::OptionExt, prelude::ResultExt, report};
 #[cfg(feature = "thirtyfour")]
 use std::collections::VecDeque;
@@ -46,14 +65,14 @@ pub enum ChromeBinary {
 }
 
 impl ChromeBinary {
-    const fn label(self) -> &'static str {
+    pub(crate) const fn label(self) -> &'static str {
         match self {
             Self::Chrome => "Chrome",
             Self::ChromeHeadlessShell => "Chrome Headless Shell",
         }
     }
 
-    const fn artifact(self) -> ChromeForTestingArtifact {
+    pub(crate) const fn artifact(self) -> ChromeForTestingArtifact {
         match self {
             Self::Chrome => ChromeForTestingArtifact::Chrome,
             Self::ChromeHeadlessShell => ChromeForTestingArtifact::ChromeHeadlessShell,
@@ -68,6 +87,77 @@ impl ChromeBinary {
     }
 }
 
+/// The path of the executable of `artifact`, relative to the platform directory.
+fn artifact_executable_path(
+    artifact: ChromeForTestingArtifact,
+    platform: Platform,
+) -> &'static Path {
+    match artifact {
+        ChromeForTestingArtifact::Chrome => platform.chrome_executable_path(),
+        ChromeForTestingArtifact::ChromeHeadlessShell => {
+            platform.chrome_headless_shell_executable_path()
+        }
+        ChromeForTestingArtifact::ChromeDriver => platform.chromedriver_executable_path(),
+    }
+}
+
+/// Whether `artifact` is extracted in `platform_dir` and has its install manifest.
+fn is_installed(
+    platform_dir: &Path,
+    artifact: ChromeForTestingArtifact,
+    platform: Platform,
+) -> bool {
+    platform_dir
+        .join(artifact_executable_path(artifact, platform))
+        .is_file()
+        && InstallManifest::path(platform_dir, artifact).is_file()
+}
+
+/// Retrieve the archive requested by `request` from `source` and extract it into `platform_dir`,
+/// replacing any previous installation, and record its install manifest.
+///
+/// The archive is retrieved and extracted in a staging directory next to the installation, and
+/// only moved into place once extraction succeeded. A killed process therefore never leaves a
+/// partial extraction behind; its staging directory is removed by a later installation.
+///
+/// Must be called while holding the [`InstallLock`] of `platform_dir`.
+async fn install_from_source(
+    source: &dyn ArtifactSource,
+    request: &ArtifactRequest,
+    platform_dir: &Path,
+    retry_policy: &RetryPolicy,
+) -> Result<(), Report<ChromeForTestingManagerError>> {
+    staging::remove_leftover_staging_dirs(platform_dir).await;
+    let staging_dir = StagingDir::create(platform_dir, request.artifact)?;
+
+    let files = download::download_zip(
+        source,
+        request,
+        staging_dir.path(),
+        staging_dir.path(),
+        retry_policy,
+    )
+    .await?;
+
+    let manifest_path = InstallManifest::path(platform_dir, request.artifact);
+    let manifest = InstallManifest::create(
+        staging_dir.path().to_owned(),
+        files,
+        request.url.clone(),
+        source.location(request),
+    )
+    .await
+    .context(ChromeForTestingManagerError::WriteInstallManifest {
+        path: manifest_path.clone(),
+    })?;
+
+    // Without its manifest, the artifact counts as not installed until the manifest of the new
+    // installation is written, so a crash while moving it into place is detected and redone.
+    staging::remove_path(&manifest_path).await?;
+    staging_dir.commit(platform_dir).await?;
+    manifest.write(&manifest_path).await
+}
+
 #[cfg(feature = "thirtyfour")]
 #[derive(Debug)]
 pub(crate) struct HeadlessShellSession {
@@ -99,28 +189,40 @@ impl HeadlessShellSession {
 /// on either regular Chrome or Chrome Headless Shell use [`LoadedBrowserPackage`] instead.
 #[derive(Debug, Clone)]
 pub struct LoadedChromePackage {
-    chrome_executable: PathBuf,
-    chromedriver_executable: PathBuf,
+    chrome: LoadedArtifact,
+    chromedriver: LoadedArtifact,
 }
 
 impl LoadedChromePackage {
-    fn new(chrome_executable: PathBuf, chromedriver_executable: PathBuf) -> Self {
+    const fn new(chrome: LoadedArtifact, chromedriver: LoadedArtifact) -> Self {
         Self {
-            chrome_executable,
-            chromedriver_executable,
+            chrome,
+            chromedriver,
         }
     }
 
     /// Path to the cached regular Chrome executable.
     #[must_use]
     pub fn chrome_executable(&self) -> &Path {
-        &self.chrome_executable
+        &self.chrome.executable
+    }
+
+    /// The version of the regular Chrome package.
+    #[must_use]
+    pub const fn chrome_version(&self) -> Version {
+        self.chrome.version
     }
 
     /// Path to the cached `ChromeDriver` executable.
     #[must_use]
     pub fn chromedriver_executable(&self) -> &Path {
-        &self.chromedriver_executable
+        &self.chromedriver.executable
+    }
+
+    /// The version of the `ChromeDriver` package.
+    #[must_use]
+    pub const fn chromedriver_version(&self) -> Version {
+        self.chromedriver.version
     }
 }
 
@@ -130,34 +232,54 @@ impl LoadedChromePackage {
 /// either regular Chrome or Chrome Headless Shell use [`LoadedBrowserPackage`] instead.
 #[derive(Debug, Clone)]
 pub struct LoadedChromeHeadlessShellPackage {
-    chrome_headless_shell_executable: PathBuf,
-    chromedriver_executable: PathBuf,
+    chrome_headless_shell: LoadedArtifact,
+    chromedriver: LoadedArtifact,
 }
 
 impl LoadedChromeHeadlessShellPackage {
-    fn new(chrome_headless_shell_executable: PathBuf, chromedriver_executable: PathBuf) -> Self {
+    const fn new(chrome_headless_shell: LoadedArtifact, chromedriver: LoadedArtifact) -> Self {
         Self {
-            chrome_headless_shell_executable,
-            chromedriver_executable,
+            chrome_headless_shell,
+            chromedriver,
         }
     }
 
     /// Path to the cached Chrome Headless Shell executable.
     #[must_use]
     pub fn chrome_headless_shell_executable(&self) -> &Path {
-        &self.chrome_headless_shell_executable
+        &self.chrome_headless_shell.executable
+    }
+
+    /// The version of the Chrome Headless Shell package.
+    #[must_use]
+    pub const fn chrome_headless_shell_version(&self) -> Version {
+        self.chrome_headless_shell.version
     }
 
     /// Path to the cached `ChromeDriver` executable.
     #[must_use]
     pub fn chromedriver_executable(&self) -> &Path {
-        &self.chromedriver_executable
+        &self.chromedriver.executable
+    }
+
+    /// The version of the `ChromeDriver` package.
+    #[must_use]
+    pub const fn chromedriver_version(&self) -> Version {
+        self.chromedriver.version
     }
 }
 
+/// A cached executable and the version of the package it was extracted from.
+#[derive(Debug, Clone)]
+struct LoadedArtifact {
+    executable: PathBuf,
+    version: Version,
+}
+
 /// A downloaded Chrome-compatible browser package paired with a matching `ChromeDriver`.
 ///
-/// Returned by [`ChromeForTestingManager::download`]. Match on the enum when behavior differs
+/// Returned by [`ChromeForTestingManager::download`] and
+/// [`ChromeForTestingManager::download_with_chromedriver`]. Match on the enum when behavior differs
 /// between regular Chrome and Chrome Headless Shell, or use [`Self::browser_executable`] and
 /// [`Self::chromedriver_executable`] for behavior shared by both browser packages.
 #[derive(Debug, Clone)]
@@ -171,18 +293,15 @@ pub enum LoadedBrowserPackage {
 }
 
 impl LoadedBrowserPackage {
-    fn new(
+    const fn new(
         chrome_binary: ChromeBinary,
-        browser_executable: PathBuf,
-        chromedriver_executable: PathBuf,
+        browser: LoadedArtifact,
+        chromedriver: LoadedArtifact,
     ) -> Self {
         match chrome_binary {
-            ChromeBinary::Chrome => Self::Chrome(LoadedChromePackage::new(
-                browser_executable,
-                chromedriver_executable,
-            )),
+            ChromeBinary::Chrome => Self::Chrome(LoadedChromePackage::new(browser, chromedriver)),
             ChromeBinary::ChromeHeadlessShell => Self::ChromeHeadlessShell(
-                LoadedChromeHeadlessShellPackage::new(browser_executable, chromedriver_executable),
+                LoadedChromeHeadlessShellPackage::new(browser, chromedriver),
             ),
         }
     }
@@ -205,6 +324,15 @@ impl LoadedBrowserPackage {
         }
     }
 
+    /// The version of the browser package.
+    #[must_use]
+    pub const fn browser_version(&self) -> Version {
+        match self {
+            Self::Chrome(package) => package.chrome_version(),
+            Self::ChromeHeadlessShell(package) => package.chrome_headless_shell_version(),
+        }
+    }
+
     /// Path to the cached `ChromeDriver` executable.
     #[must_use]
     pub fn chromedriver_executable(&self) -> &Path {
@@ -213,6 +341,16 @@ impl LoadedBrowserPackage {
             Self::ChromeHeadlessShell(package) => package.chromedriver_executable(),
         }
     }
+
+    /// The version of the `ChromeDriver` package. Differs from [`Self::browser_version`] when
+    /// downloaded through [`ChromeForTestingManager::download_with_chromedriver`].
+    #[must_use]
+    pub const fn chromedriver_version(&self) -> Version {
+        match self {
+            Self::Chrome(package) => package.chromedriver_version(),
+            Self::ChromeHeadlessShell(package) => package.chromedriver_version(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -251,7 +389,8 @@ impl RequestedChromeBinaries {
 
 #[derive(Debug)]
 struct DownloadedBrowserArtifacts {
-    chromedriver: PathBuf,
+    chromedriver: LoadedArtifact,
+    browser_version: Version,
     chrome: Option<PathBuf>,
     chrome_headless_shell: Option<PathBuf>,
 }
@@ -260,16 +399,16 @@ impl DownloadedBrowserArtifacts {
     fn package_for(
         &self,
         chrome_binary: ChromeBinary,
-        version: Version,
         platform: Platform,
     ) -> Result<LoadedBrowserPackage, Report<ChromeForTestingManagerError>> {
-        let browser_executable = self
-            .browser_executable(chrome_binary, version, platform)?
-            .clone();
+        let browser = LoadedArtifact {
+            executable: self.browser_executable(chrome_binary, platform)?.clone(),
+            version: self.browser_version,
+        };
 
         Ok(LoadedBrowserPackage::new(
             chrome_binary,
-            browser_executable,
+            browser,
             self.chromedriver.clone(),
         ))
     }
@@ -277,9 +416,9 @@ impl DownloadedBrowserArtifacts {
     fn browser_executable(
         &self,
         chrome_binary: ChromeBinary,
-        version: Version,
         platform: Platform,
     ) -> Result<&PathBuf, Report<ChromeForTestingManagerError>> {
+        let version = self.browser_version;
         match chrome_binary {
             ChromeBinary::Chrome => self.chrome.as_ref().ok_or_else(|| {
                 report!(ChromeForTestingManagerError::NoChromeDownload { version, platform })
@@ -310,13 +449,37 @@ impl DownloadedBrowserArtifacts {
 ///   [`Self::launch_chromedriver`] repeatedly with the same [`LoadedBrowserPackage`].
 /// - **Inspect or modify the resolved version** before downloading (channel, available platforms).
 /// - **Pin a custom cache directory** via [`Self::new_with_cache_dir`] (useful in CI).
+/// - **Use an internal mirror** of the release index and artifacts via [`Self::with_mirror`].
+/// - **Retrieve artifacts from elsewhere**, e.g. a build cache or fixture archives, via
+///   [`Self::with_artifact_source`].
+/// - **Serve the release index from elsewhere**, e.g. a file share or a fixed test fixture, via
+///   [`Self::with_release_index_source`].
+/// - **Pin resolved versions across a team** via [`Self::with_lockfile`], and learn when they
+///   fall behind stable via [`Self::with_staleness_check`].
+/// - **Prepare artifacts for another platform** via [`Self::with_platform`].
+/// - **Work without network access** via [`Self::with_offline`], resolving versions against what
+///   is already installed in the cache.
 /// - **Drive sessions through a non-`thirtyfour`** `WebDriver` client by using the chromedriver
 ///   process and port directly.
 #[derive(Debug)]
 pub struct ChromeForTestingManager {
     client: reqwest::Client,
     cache_dir: CacheDir,
-    platform: Platform,
+    index_cache: ReleaseIndexCache,
+    index_ttl: Duration,
+    mirror: Mirror,
+    index_source: Option<Arc<dyn ReleaseIndexSource>>,
+    artifact_source: Option<Arc<dyn ArtifactSource>>,
+    lockfile: Option<Lockfile>,
+    staleness_check: Option<StalenessCheck>,
+    channel_heads: bool,
+    install_lock_timeout: Duration,
+    retry_policy: RetryPolicy,
+    /// `None` on a host unsupported by chrome-for-testing, unless set via [`Self::with_platform`].
+    platform: Option<Platform>,
+    /// `None` if unsupported by chrome-for-testing.
+    host_platform: Option<Platform>,
+    offline: bool,
 }
 
 impl ChromeForTestingManager {
@@ -324,14 +487,9 @@ impl ChromeForTestingManager {
     ///
     /// # Errors
     ///
-    /// Returns an error if the current platform is unsupported or the cache directory
-    /// cannot be determined or created.
+    /// Returns an error if the cache directory cannot be determined or created.
     pub fn new() -> Result<Self, Report<ChromeForTestingManagerError>> {
-        Ok(Self {
-            client: reqwest::Client::new(),
-            cache_dir: CacheDir::get_or_create()?,
-            platform: Platform::detect().map_err(unsupported_platform_error)?,
-        })
+        Ok(Self::with_cache(CacheDir::get_or_create()?))
     }
 
     /// Create a manager that caches downloaded artifacts under `cache_dir`.
@@ -341,30 +499,265 @@ impl ChromeForTestingManager {
     ///
     /// # Errors
     ///
-    /// Returns an error if the current platform is unsupported or the directory cannot be created.
+    /// Returns an error if the directory cannot be created.
     pub fn new_with_cache_dir(
         cache_dir: PathBuf,
     ) -> Result<Self, Report<ChromeForTestingManagerError>> {
-        Ok(Self {
+        Ok(Self::with_cache(CacheDir::create_at(cache_dir)?))
+    }
+
+    fn with_cache(cache_dir: CacheDir) -> Self {
+        let host_platform = Platform::detect()
+            .inspect_err(|err| {
+                tracing::debug!("The host platform is unsupported by chrome-for-testing: {err}");
+            })
+            .ok();
+        Self {
             client: reqwest::Client::new(),
-            cache_dir: CacheDir::create_at(cache_dir)?,
-            platform: Platform::detect().map_err(unsupported_platform_error)?,
+            index_cache: ReleaseIndexCache::new(cache_dir.index_dir()),
+            cache_dir,
+            index_ttl: DEFAULT_INDEX_TTL,
+            mirror: Mirror::default(),
+            index_source: None,
+            artifact_source: None,
+            lockfile: None,
+            staleness_check: None,
+            channel_heads: false,
+            install_lock_timeout: DEFAULT_INSTALL_LOCK_TIMEOUT,
+            retry_policy: RetryPolicy::default(),
+            platform: host_platform,
+            host_platform,
+            offline: false,
+        }
+    }
+
+    /// Enable or disable offline mode.
+    ///
+    /// In offline mode the manager never touches the network. [`Self::resolve_version`] resolves
+    /// requests against the versions already installed in the cache directory, and
+    /// [`Self::download`] fails for artifacts that are not installed yet.
+    #[must_use]
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Whether this manager operates in offline mode.
+    #[must_use]
+    pub const fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    /// Set how long fetched release-index documents are reused before being revalidated.
+    ///
+    /// The chrome-for-testing release index is cached in the cache directory together with its
+    /// fetch time and `ETag`. Within `ttl`, [`Self::resolve_version`] reuses the cached copy
+    /// without any network access. Afterward, the copy is revalidated with a conditional request,
+    /// and a transient network error falls back to the stale copy. Defaults to one hour. Pass
+    /// [`Duration::ZERO`] to revalidate on every resolution.
+    #[must_use]
+    pub fn with_index_ttl(mut self, ttl: Duration) -> Self {
+        self.index_ttl = ttl;
+        self
+    }
+
+    /// How long fetched release-index documents are reused before being revalidated.
+    #[must_use]
+    pub const fn index_ttl(&self) -> Duration {
+        self.index_ttl
+    }
+
+    /// Fetch the release index and artifacts from `mirror` instead of Google's hosts.
+    ///
+    /// [`SelectedVersion`] keeps reporting the upstream download URLs. Artifact URLs are only
+    /// rewritten when downloading.
+    #[must_use]
+    pub fn with_mirror(mut self, mirror: Mirror) -> Self {
+        self.mirror = mirror;
+        self
+    }
+
+    /// The mirror used for release-index and artifact requests.
+    #[must_use]
+    pub const fn mirror(&self) -> &Mirror {
+        &self.mirror
+    }
+
+    /// Load the release index from `source` instead of the chrome-for-testing JSON API.
+    ///
+    /// Documents loaded from a custom source bypass the release-index cache, so
+    /// [`Self::with_index_ttl`] and the index base URL of [`Self::with_mirror`] no longer apply.
+    /// Failed loads are retried according to [`Self::with_retry_policy`], whatever the source.
+    /// Offline mode still resolves against the installed versions only.
+    #[must_use]
+    pub fn with_release_index_source(mut self, source: impl ReleaseIndexSource + 'static) -> Self {
+        self.index_source = Some(Arc::new(source));
+        self
+    }
+
+    /// The custom release-index source, if any.
+    #[must_use]
+    pub fn release_index_source(&self) -> Option<&dyn ReleaseIndexSource> {
+        self.index_source.as_deref()
+    }
+
+    /// Retrieve artifact archives from `source` instead of downloading them over HTTP.
+    ///
+    /// The artifact base URL of [`Self::with_mirror`] no longer applies. Offline mode still never
+    /// retrieves missing artifacts.
+    #[must_use]
+    pub fn with_artifact_source(mut self, source: impl ArtifactSource + 'static) -> Self {
+        self.artifact_source = Some(Arc::new(source));
+        self
+    }
+
+    /// The custom artifact source, if any.
+    #[must_use]
+    pub fn artifact_source(&self) -> Option<&dyn ArtifactSource> {
+        self.artifact_source.as_deref()
+    }
+
+    /// Resolve and download artifacts for `platform` instead of the host platform.
+    ///
+    /// Useful to prepare Docker images or air-gapped CI bundles for another platform, e.g.
+    /// `linux64` artifacts from a Mac. Artifacts land in the `<version>/<platform>` directory of
+    /// the cache. Binaries for a foreign platform cannot be launched:
+    /// [`Self::launch_chromedriver`] fails with
+    /// [`ChromeForTestingManagerError::ForeignPlatform`].
+    ///
+    /// Also works on hosts unsupported by chrome-for-testing, which have no platform to default to.
+    #[must_use]
+    pub fn with_platform(mut self, platform: Platform) -> Self {
+        self.platform = Some(platform);
+        self
+    }
+
+    /// The platform artifacts are resolved and downloaded for. Defaults to the host platform.
+    ///
+    /// `None` on a host unsupported by chrome-for-testing, unless set via [`Self::with_platform`].
+    #[must_use]
+    pub const fn platform(&self) -> Option<Platform> {
+        self.platform
+    }
+
+    /// The platform artifacts are resolved and downloaded for, failing with
+    /// [`ChromeForTestingManagerError::UnsupportedPlatform`] if there is none.
+    fn target_platform(&self) -> Result<Platform, Report<ChromeForTestingManagerError>> {
+        self.platform.ok_or_else(|| {
+            report!(ChromeForTestingManagerError::UnsupportedPlatform).attach(
+                "select a supported target platform via `ChromeForTestingManager::with_platform`",
+            )
         })
     }
 
+    fn ensure_host_platform(&self) -> Result<(), Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        match self.host_platform {
+            Some(host_platform) if host_platform == platform => Ok(()),
+            Some(host_platform) => bail!(ChromeForTestingManagerError::ForeignPlatform {
+                platform,
+                host_platform,
+            }),
+            None => Err(report!(ChromeForTestingManagerError::UnsupportedPlatform)
+                .attach(format!("cannot launch {platform} binaries on this host"))),
+        }
+    }
+
+    /// Pin resolved versions in `lockfile`. See [`Lockfile`].
+    #[must_use]
+    pub fn with_lockfile(mut self, lockfile: Lockfile) -> Self {
+        self.lockfile = Some(lockfile);
+        self
+    }
+
+    /// The lockfile pinning resolved versions, if any.
+    #[must_use]
+    pub const fn lockfile(&self) -> Option<&Lockfile> {
+        self.lockfile.as_ref()
+    }
+
+    /// Check every resolved version against the current stable release. See [`StalenessCheck`].
+    #[must_use]
+    pub fn with_staleness_check(mut self, check: StalenessCheck) -> Self {
+        self.staleness_check = Some(check);
+        self
+    }
+
+    /// The staleness check run on resolved versions, if any.
+    #[must_use]
+    pub const fn staleness_check(&self) -> Option<StalenessCheck> {
+        self.staleness_check
+    }
+
+    /// Enable or disable looking up the channels resolved versions currently head.
+    ///
+    /// When enabled, [`Self::resolve_version`] fetches the last-known-good index to fill in
+    /// [`SelectedVersion::head_of_channels`], except for versions reused from a lockfile. Disabled
+    /// by default.
+    #[must_use]
+    pub fn with_channel_heads(mut self, enabled: bool) -> Self {
+        self.channel_heads = enabled;
+        self
+    }
+
+    /// Whether resolved versions report the channels they currently head.
+    #[must_use]
+    pub const fn reports_channel_heads(&self) -> bool {
+        self.channel_heads
+    }
+
+    /// Set how long to wait for other processes installing the same version.
+    ///
+    /// Installs into a `<version>/<platform>` directory of the cache are serialized across
+    /// processes by an advisory lock file, so concurrently started test binaries download each
+    /// artifact once and reuse each other's installation. Defaults to ten minutes. A lock whose
+    /// holder stopped touching it for two minutes, e.g. a hung process, is taken over.
+    #[must_use]
+    pub fn with_install_lock_timeout(mut self, timeout: Duration) -> Self {
+        self.install_lock_timeout = timeout;
+        self
+    }
+
+    /// How long to wait for other processes installing the same version.
+    #[must_use]
+    pub const fn install_lock_timeout(&self) -> Duration {
+        self.install_lock_timeout
+    }
+
+    /// Retry failed release-index fetches and artifact downloads according to `policy`.
+    ///
+    /// Defaults to [`RetryPolicy::default`]. Pass [`RetryPolicy::none`] to fail on the first
+    /// error.
+    #[must_use]
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// The policy failed release-index fetches and artifact downloads are retried with.
+    #[must_use]
+    pub const fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
     fn version_dir(&self, version: Version) -> PathBuf {
         self.cache_dir.path().join(version.to_string())
     }
 
-    fn platform_dir(&self, version: Version) -> PathBuf {
-        self.version_dir(version).join(self.platform.to_string())
+    fn platform_dir(
+        &self,
+        version: Version,
+    ) -> Result<PathBuf, Report<ChromeForTestingManagerError>> {
+        Ok(self
+            .version_dir(version)
+            .join(self.target_platform()?.to_string()))
     }
 
     async fn ensure_platform_dir(
         &self,
         version: Version,
     ) -> Result<PathBuf, Report<ChromeForTestingManagerError>> {
-        let platform_dir = self.platform_dir(version);
+        let platform_dir = self.platform_dir(version)?;
         fs::create_dir_all(&platform_dir).await.context(
             ChromeForTestingManagerError::CreatePlatformDir {
                 platform_dir: platform_dir.clone(),
@@ -380,47 +773,278 @@ impl ChromeForTestingManager {
         self.cache_dir.clear().await
     }
 
+    /// List all versions in the chrome-for-testing release index offering any download for
+    /// `platform`, oldest first.
+    ///
+    /// Each entry reports which artifacts can be downloaded for `platform` and which channels it
+    /// currently is the latest release of. Useful for tooling and for choosing version pins
+    /// programmatically.
+    ///
+    /// In offline mode, the versions installed in the cache directory are listed instead, without
+    /// channel information. Other platforms than the current one have no installed versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release index cannot be fetched, or the installed versions cannot be
+    /// listed in offline mode.
+    pub async fn available_versions(
+        &self,
+        platform: Platform,
+    ) -> Result<Vec<AvailableVersion>, Report<ChromeForTestingManagerError>> {
+        if self.offline {
+            if Some(platform) != self.platform {
+                return Ok(Vec::new());
+            }
+            let mut installed = self.installed_versions().await?;
+            installed.sort();
+            return installed
+                .into_iter()
+                .map(|version| {
+                    let selected = self.installed_selected_version(version)?;
+                    Ok(AvailableVersion {
+                        version,
+                        revision: None,
+                        channels: Vec::new(),
+                        chrome: selected.has_chrome_download(),
+                        chrome_headless_shell: selected.has_chrome_headless_shell_download(),
+                        chromedriver: selected.has_chromedriver_download(),
+                    })
+                })
+                .collect();
+        }
+
+        let (known_good, last_known_good) = tokio::try_join!(
+            self.fetch_index::<KnownGoodVersions>(ReleaseIndexDocument::KnownGoodVersions),
+            self.fetch_index::<LastKnownGoodVersions>(ReleaseIndexDocument::LastKnownGoodVersions),
+        )?;
+        Ok(available_versions(&known_good, &last_known_good, platform))
+    }
+
     /// Resolve a [`VersionRequest`] against the chrome-for-testing release index.
     ///
     /// Returns a [`SelectedVersion`] suitable for [`Self::download`]. No artifacts are downloaded
     /// at this point; this only performs the HTTP requests needed to determine which version to
-    /// fetch.
+    /// fetch. Release-index documents are cached; see [`Self::with_index_ttl`].
+    ///
+    /// In offline mode, the request is resolved against the installed versions instead. See
+    /// [`Self::with_offline`].
+    ///
+    /// With a lockfile configured, a version pinned for the same request is reused. See
+    /// [`Self::with_lockfile`].
+    ///
+    /// Outside offline mode, the optional staleness check and channel-head lookup compare the
+    /// selected version against the last-known-good index. See [`Self::with_staleness_check`] and
+    /// [`Self::with_channel_heads`].
     ///
     /// # Errors
     ///
-    /// Returns an error if the version manifest cannot be fetched or no matching version exists.
+    /// Returns an error if the version manifest cannot be fetched, no matching version exists, or
+    /// the lockfile cannot be read or written.
     pub async fn resolve_version(
         &self,
         version_selection: VersionRequest,
     ) -> Result<SelectedVersion, Report<ChromeForTestingManagerError>> {
+        let (mut selected, pinned) = match &self.lockfile {
+            Some(lockfile) => {
+                self.resolve_locked_version(lockfile, version_selection)
+                    .await?
+            }
+            None => (
+                self.resolve_unlocked_version(version_selection).await?,
+                false,
+            ),
+        };
+        let lookup_channel_heads = self.channel_heads && !pinned;
+        if !self.offline && (lookup_channel_heads || self.staleness_check.is_some()) {
+            self.annotate_from_last_known_good(&mut selected).await;
+        }
+        Ok(selected)
+    }
+
+    /// If enabled, look up the channels `selected` currently heads and run the staleness check.
+    ///
+    /// Leaves both unset if the last-known-good index cannot be fetched, which is not worth
+    /// failing the resolution over.
+    async fn annotate_from_last_known_good(&self, selected: &mut SelectedVersion) {
+        let version = selected.version;
+        let last_known_good = match self
+            .fetch_index::<LastKnownGoodVersions>(ReleaseIndexDocument::LastKnownGoodVersions)
+            .await
+        {
+            Ok(last_known_good) => last_known_good,
+            Err(err) => {
+                tracing::warn!("Could not compare {version} against the channel heads: {err}");
+                return;
+            }
+        };
+        if self.channel_heads {
+            selected.head_of_channels = Some(
+                channel_heads(&last_known_good)
+                    .into_iter()
+                    .filter(|head| head.version == version)
+                    .map(|head| head.channel.clone())
+                    .collect(),
+            );
+        }
+        if let Some(check) = self.staleness_check
+            && let Some(stable) = last_known_good.stable()
+        {
+            let advisory = check.check(version, stable.version);
+            advisory.warn_if_stale();
+            selected.staleness = Some(advisory);
+        }
+    }
+
+    /// Resolve `version_selection` through `lockfile`, pinning newly resolved versions.
+    ///
+    /// Also returns whether the version was already pinned.
+    async fn resolve_locked_version(
+        &self,
+        lockfile: &Lockfile,
+        version_selection: VersionRequest,
+    ) -> Result<(SelectedVersion, bool), Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let request_key = lockfile::request_key(&version_selection).context(
+            ChromeForTestingManagerError::UnpinnableVersionRequest {
+                version_request: version_selection.clone(),
+            },
+        )?;
+        let locked = if lockfile.is_update() {
+            None
+        } else {
+            lockfile.read().await?
+        };
+
+        let locked = match locked {
+            Some(mut locked) if locked.is_for(&request_key) => {
+                if let Some(selected) = locked.selected_version(platform) {
+                    tracing::info!(
+                        "Using version {} pinned in {:?}.",
+                        selected.version,
+                        lockfile.path()
+                    );
+                    return Ok((selected, true));
+                }
+                let selected = self
+                    .resolve_unlocked_version(VersionRequest::Fixed(locked.version()))
+                    .await?;
+                locked.record(&selected, platform);
+                tracing::info!(
+                    "Recording {} downloads of pinned version {} in {:?}.",
+                    platform,
+                    selected.version,
+                    lockfile.path()
+                );
+                lockfile.write(&locked).await?;
+                let selected = locked.selected_version(platform).context(
+                    ChromeForTestingManagerError::NoMatchingVersion {
+                        version_request: version_selection,
+                    },
+                )?;
+                return Ok((selected, true));
+            }
+            locked => locked,
+        };
+        if locked.is_some() {
+            tracing::info!(
+                "Version request changed to {version_selection:?}; replacing the version pinned in {:?}.",
+                lockfile.path()
+            );
+        }
+
+        let selected = self
+            .resolve_unlocked_version(version_selection.clone())
+            .await?;
+        tracing::info!(
+            "Pinning version {} in {:?}.",
+            selected.version,
+            lockfile.path()
+        );
+        lockfile
+            .write(&LockedVersion::new(request_key, &selected, platform))
+            .await?;
+        Ok((selected, false))
+    }
+
+    pub(crate) async fn resolve_unlocked_version(
+        &self,
+        version_selection: VersionRequest,
+    ) -> Result<SelectedVersion, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        if self.offline {
+            return self.resolve_installed_version(version_selection).await;
+        }
+
         let selected = match &version_selection {
             VersionRequest::Latest => {
-                let all = KnownGoodVersions::fetch(&self.client)
+                let all = self
+                    .fetch_index::<KnownGoodVersions>(ReleaseIndexDocument::KnownGoodVersions)
                     .await
-                    .map_err(|err| request_versions_error(err, &version_selection))?;
+                    .context(ChromeForTestingManagerError::RequestVersions {
+                        version_request: version_selection.clone(),
+                    })?;
                 all.versions
                     .iter()
                     .filter(|v| v.downloads.chromedriver.is_some())
                     .max_by_key(|v| v.version)
                     .cloned()
-                    .map(|v| SelectedVersion::from((v, self.platform)))
+                    .map(|v| SelectedVersion::from((v, platform)))
             }
             VersionRequest::LatestIn(channel) => {
-                let all = LastKnownGoodVersions::fetch(&self.client)
+                let all = self
+                    .fetch_index::<LastKnownGoodVersions>(
+                        ReleaseIndexDocument::LastKnownGoodVersions,
+                    )
                     .await
-                    .map_err(|err| request_versions_error(err, &version_selection))?;
+                    .context(ChromeForTestingManagerError::RequestVersions {
+                        version_request: version_selection.clone(),
+                    })?;
                 all.channel(channel)
                     .cloned()
-                    .map(|v| SelectedVersion::from((v, self.platform)))
+                    .map(|v| SelectedVersion::from((v, platform)))
             }
-            VersionRequest::Fixed(version) => {
-                let all = KnownGoodVersions::fetch(&self.client)
-                    .await
-                    .map_err(|err| request_versions_error(err, &version_selection))?;
-                all.versions
-                    .into_iter()
-                    .find(|v| v.version == *version)
-                    .map(|v| SelectedVersion::from((v, self.platform)))
+            VersionRequest::Fixed(version) => self.resolve_fixed(*version).await.context(
+                ChromeForTestingManagerError::RequestVersions {
+                    version_request: version_selection.clone(),
+                },
+            )?,
+            VersionRequest::Milestone(milestone) => self
+                .resolve_milestone(*milestone)
+                .await
+                .context(ChromeForTestingManagerError::RequestVersions {
+                    version_request: version_selection.clone(),
+                })?,
+            VersionRequest::StableMinus(n) => self.resolve_stable_minus(*n).await.context(
+                ChromeForTestingManagerError::RequestVersions {
+                    version_request: version_selection.clone(),
+                },
+            )?,
+            VersionRequest::SystemChrome => {
+                let system_chrome = SystemChrome::detect().await.context(
+                    ChromeForTestingManagerError::RequestVersions {
+                        version_request: version_selection.clone(),
+                    },
+                )?;
+                self.resolve_system_chrome(system_chrome).await.context(
+                    ChromeForTestingManagerError::RequestVersions {
+                        version_request: version_selection.clone(),
+                    },
+                )?
+            }
+            VersionRequest::LatestInstalled => self
+                .installed_versions()
+                .await?
+                .into_iter()
+                .max()
+                .map(|version| self.installed_selected_version(version))
+                .transpose()?,
+            VersionRequest::FirstOf(candidates) => {
+                return self
+                    .resolve_first_of(candidates, version_selection.clone())
+                    .await;
+            }
+            VersionRequest::Matching(filter) => {
+                return self.resolve_matching(filter, &version_selection).await;
             }
         };
 
@@ -431,70 +1055,554 @@ impl ChromeForTestingManager {
         Ok(selected)
     }
 
-    /// Download the requested browser artifact(s) and matching `ChromeDriver`.
-    ///
-    /// Returns one [`LoadedBrowserPackage`] per requested [`ChromeBinary`], in request order. The
-    /// download work is de-duplicated, so repeated binary values do not download the same artifact
-    /// more than once.
-    ///
-    /// # Errors
+    /// Resolve the newest known-good version satisfying `filter`, reporting which constraint
+    /// excluded which candidates should none do.
+    async fn resolve_matching(
+        &self,
+        filter: &VersionFilter,
+        version_selection: &VersionRequest,
+    ) -> Result<SelectedVersion, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let all = self
+            .fetch_index::<KnownGoodVersions>(ReleaseIndexDocument::KnownGoodVersions)
+            .await
+            .context(ChromeForTestingManagerError::RequestVersions {
+                version_request: version_selection.clone(),
+            })?;
+        let candidates = all
+            .versions
+            .into_iter()
+            .map(|v| SelectedVersion::from((v, platform)));
+        filter.select(candidates).map_err(|exclusions| {
+            attach_exclusions(
+                report!(ChromeForTestingManagerError::NoMatchingVersion {
+                    version_request: version_selection.clone(),
+                }),
+                exclusions,
+            )
+        })
+    }
+
+    /// Resolve `version` through the known-good-versions index.
     ///
-    /// Returns an error if `chrome_binaries` is empty, no platform-matching browser or
-    /// `ChromeDriver` download exists, the cache directory cannot be prepared, or the download /
-    /// extraction fails.
-    pub async fn download(
+    /// Should the index be unreachable, or only the embedded snapshot be available and not list
+    /// `version`, the version is assumed to exist at the predictable chrome-for-testing download
+    /// URLs. Downloading then fails if it does not.
+    async fn resolve_fixed(
         &self,
-        selected: &SelectedVersion,
-        chrome_binaries: &[ChromeBinary],
-    ) -> Result<Vec<LoadedBrowserPackage>, Report<ChromeForTestingManagerError>> {
-        let requested = RequestedChromeBinaries::from_slice(chrome_binaries)?;
-        let artifacts = self
-            .download_requested_artifacts(selected, requested)
-            .await?;
-        let mut loaded = Vec::with_capacity(chrome_binaries.len());
-        for chrome_binary in chrome_binaries {
-            loaded.push(artifacts.package_for(*chrome_binary, selected.version, self.platform)?);
+        version: Version,
+    ) -> Result<Option<SelectedVersion>, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let all = match self
+            .fetch_index::<KnownGoodVersions>(ReleaseIndexDocument::KnownGoodVersions)
+            .await
+        {
+            Ok(all) => all,
+            Err(err) if retry::is_unreachable(&err) => {
+                tracing::warn!(
+                    "Release index is unreachable, assuming {version} exists at its canonical download URLs: {err}"
+                );
+                return Ok(Some(SelectedVersion::canonical(version, platform)));
+            }
+            Err(err) => return Err(err),
+        };
+        let is_embedded_snapshot = index::is_embedded_snapshot(&all);
+        let selected = all
+            .versions
+            .into_iter()
+            .find(|v| v.version == version)
+            .map(|v| SelectedVersion::from((v, platform)));
+        if selected.is_none() && is_embedded_snapshot {
+            tracing::warn!(
+                "{version} is not listed in the embedded release-index snapshot, assuming it exists at its canonical download URLs."
+            );
+            return Ok(Some(SelectedVersion::canonical(version, platform)));
         }
-
-        Ok(loaded)
+        Ok(selected)
     }
 
-    pub(crate) async fn download_one(
+    /// Resolve the newest build of `milestone` that has a `ChromeDriver` download for the platform.
+    ///
+    /// The latest-versions-per-milestone index only lists the newest build of each milestone.
+    /// Should that build lack a `ChromeDriver` for the platform, older builds of the milestone are
+    /// looked up in the known-good-versions index.
+    async fn resolve_milestone(
         &self,
-        selected: &SelectedVersion,
-        chrome_binary: ChromeBinary,
-    ) -> Result<LoadedBrowserPackage, Report<ChromeForTestingManagerError>> {
+        milestone: u32,
+    ) -> Result<Option<SelectedVersion>, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let per_milestone = self
+            .fetch_index::<LatestVersionsPerMilestone>(
+                ReleaseIndexDocument::LatestVersionsPerMilestone,
+            )
+            .await?;
+        let latest = per_milestone
+            .milestone(milestone)
+            .filter(|v| v.downloads.chromedriver_for_platform(platform).is_some());
+
+        let selected = if let Some(latest) = latest {
+            Some(SelectedVersion::from((latest.clone(), platform)))
+        } else {
+            let all = self
+                .fetch_index::<KnownGoodVersions>(ReleaseIndexDocument::KnownGoodVersions)
+                .await?;
+            all.versions
+                .into_iter()
+                .filter(|v| {
+                    v.version.major == milestone
+                        && v.downloads.chromedriver_for_platform(platform).is_some()
+                })
+                .max_by_key(|v| v.version)
+                .map(|v| SelectedVersion::from((v, platform)))
+        };
+
+        Ok(selected.map(|mut selected| {
+            selected.milestone = Some(milestone);
+            selected
+        }))
+    }
+
+    /// Resolve the first of `candidates` that has a `ChromeDriver` download for the platform.
+    ///
+    /// The rejection of every earlier candidate is logged. Should all candidates be rejected, the
+    /// rejections become the children of the returned report.
+    async fn resolve_first_of(
+        &self,
+        candidates: &[VersionRequest],
+        version_selection: VersionRequest,
+    ) -> Result<SelectedVersion, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let mut rejections = ReportCollection::new();
+        for candidate in candidates {
+            let rejection = match Box::pin(self.resolve_unlocked_version(candidate.clone())).await {
+                Ok(selected) if selected.has_chromedriver_download() => {
+                    tracing::info!(
+                        "Resolved {version_selection:?} through candidate {candidate:?} to version {}.",
+                        selected.version
+                    );
+                    return Ok(selected);
+                }
+                Ok(selected) => report!(ChromeForTestingManagerError::NoMatchingVersion {
+                    version_request: candidate.clone(),
+                })
+                .attach(format!(
+                    "version {} has no ChromeDriver download for {}",
+                    selected.version, platform
+                )),
+                Err(err) => err,
+            };
+            tracing::warn!(
+                "Rejected version candidate {candidate:?}: {}",
+                rejection.current_context()
+            );
+            rejections.push(rejection.into_cloneable());
+        }
+
+        let mut err = rejections.context(ChromeForTestingManagerError::NoMatchingVersion {
+            version_request: version_selection,
+        });
+        if candidates.is_empty() {
+            err = err.attach("no candidates to choose from");
+        }
+        Err(err)
+    }
+
+    /// Resolve the newest build of the milestone `n` majors before the current stable release.
+    async fn resolve_stable_minus(
+        &self,
+        n: u32,
+    ) -> Result<Option<SelectedVersion>, Report<ChromeForTestingManagerError>> {
+        let last_known_good = self
+            .fetch_index::<LastKnownGoodVersions>(ReleaseIndexDocument::LastKnownGoodVersions)
+            .await?;
+        let Some(stable) = last_known_good.stable() else {
+            return Ok(None);
+        };
+        let Some(milestone) = stable.version.major.checked_sub(n) else {
+            return Ok(None);
+        };
+        tracing::debug!(
+            "Current stable release is {}; resolving milestone {milestone}.",
+            stable.version
+        );
+        self.resolve_milestone(milestone).await
+    }
+
+    /// Resolve the build of `system_chrome`'s milestone closest to its version that has a
+    /// `ChromeDriver` download for the platform.
+    async fn resolve_system_chrome(
+        &self,
+        system_chrome: SystemChrome,
+    ) -> Result<Option<SelectedVersion>, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let target = system_chrome.version();
+        let all = self
+            .fetch_index::<KnownGoodVersions>(ReleaseIndexDocument::KnownGoodVersions)
+            .await?;
+        let mut candidates = all
+            .versions
+            .into_iter()
+            .filter(|v| {
+                v.version.major == target.major
+                    && v.downloads.chromedriver_for_platform(platform).is_some()
+            })
+            .collect::<Vec<_>>();
+        let Some(closest) = closest_version(target, candidates.iter().map(|v| v.version)) else {
+            return Ok(None);
+        };
+        candidates.retain(|v| v.version == closest);
+        tracing::info!(
+            "Matched system Chrome {target} at {:?} to version {closest}.",
+            system_chrome.executable()
+        );
+
+        Ok(candidates.pop().map(|v| {
+            let mut selected = SelectedVersion::from((v, platform));
+            selected.milestone = Some(target.major);
+            selected.system_chrome = Some(system_chrome);
+            selected
+        }))
+    }
+
+    async fn fetch_index<T: serde::de::DeserializeOwned>(
+        &self,
+        document: ReleaseIndexDocument,
+    ) -> Result<T, Report<ChromeForTestingManagerError>> {
+        if let Some(source) = &self.index_source {
+            let location = source.location(document);
+            let body = self
+                .retry_policy
+                .run(
+                    || ChromeForTestingManagerError::FetchReleaseIndex {
+                        location: location.clone(),
+                    },
+                    || source.load(document),
+                )
+                .await?;
+            return index::parse_index(&body, &location);
+        }
+        let base_url = self.mirror.index_base_url();
+        let body = index::fetch_index_body(
+            &self.client,
+            Some(&self.index_cache),
+            self.index_ttl,
+            base_url,
+            document,
+            &self.retry_policy,
+        )
+        .await?;
+        index::parse_index(&body, &document.url(base_url))
+    }
+
+    /// Resolve a [`VersionRequest`] against the versions installed in the cache directory.
+    ///
+    /// Channel membership cannot be verified without the release index, so
+    /// [`VersionRequest::Latest`] and [`VersionRequest::LatestIn`] both pick the newest installed
+    /// version. Likewise, [`VersionRequest::StableMinus`] counts back from the newest installed
+    /// milestone.
+    async fn resolve_installed_version(
+        &self,
+        version_selection: VersionRequest,
+    ) -> Result<SelectedVersion, Report<ChromeForTestingManagerError>> {
+        let installed = self.installed_versions().await?;
+        let mut system_chrome = None;
+        let selected = match &version_selection {
+            VersionRequest::Latest
+            | VersionRequest::LatestIn(_)
+            | VersionRequest::LatestInstalled => installed.into_iter().max(),
+            VersionRequest::Fixed(version) => installed.into_iter().find(|v| v == version),
+            VersionRequest::Milestone(milestone) => installed
+                .into_iter()
+                .filter(|v| v.major == *milestone)
+                .max(),
+            VersionRequest::StableMinus(n) => installed
+                .iter()
+                .map(|v| v.major)
+                .max()
+                .and_then(|newest| newest.checked_sub(*n))
+                .and_then(|milestone| {
+                    installed
+                        .iter()
+                        .copied()
+                        .filter(|v| v.major == milestone)
+                        .max()
+                }),
+            VersionRequest::FirstOf(candidates) => {
+                return self
+                    .resolve_first_of(candidates, version_selection.clone())
+                    .await;
+            }
+            VersionRequest::SystemChrome => {
+                let detected = system_chrome.insert(SystemChrome::detect().await?);
+                let target = detected.version();
+                closest_version(
+                    target,
+                    installed.into_iter().filter(|v| v.major == target.major),
+                )
+            }
+            VersionRequest::Matching(filter) => {
+                let candidates = installed
+                    .into_iter()
+                    .map(|version| self.installed_selected_version(version))
+                    .collect::<Result<Vec<_>, _>>()?;
+                match filter.select(candidates) {
+                    Ok(selected) => Some(selected.version),
+                    Err(exclusions) => {
+                        return Err(attach_exclusions(
+                            report!(ChromeForTestingManagerError::NoInstalledVersion {
+                                version_request: version_selection,
+                                cache_dir: self.cache_dir.path().clone(),
+                            }),
+                            exclusions,
+                        ));
+                    }
+                }
+            }
+        };
+
+        let Some(version) = selected else {
+            bail!(ChromeForTestingManagerError::NoInstalledVersion {
+                version_request: version_selection,
+                cache_dir: self.cache_dir.path().clone(),
+            });
+        };
+        tracing::info!(
+            "Resolved {version_selection:?} to installed version {version} (offline mode)."
+        );
+
+        let mut selected = self.installed_selected_version(version)?;
+        if let VersionRequest::Milestone(_)
+        | VersionRequest::StableMinus(_)
+        | VersionRequest::SystemChrome = version_selection
+        {
+            selected.milestone = Some(version.major);
+        }
+        selected.system_chrome = system_chrome;
+        Ok(selected)
+    }
+
+    /// List all versions for which a `ChromeDriver` is installed on this platform.
+    async fn installed_versions(
+        &self,
+    ) -> Result<Vec<Version>, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let cache_dir = self.cache_dir.path();
+        let mut entries = fs::read_dir(cache_dir).await.context(
+            ChromeForTestingManagerError::ListInstalledVersions {
+                cache_dir: cache_dir.clone(),
+            },
+        )?;
+
+        let mut versions = Vec::new();
+        while let Some(entry) = entries.next_entry().await.context(
+            ChromeForTestingManagerError::ListInstalledVersions {
+                cache_dir: cache_dir.clone(),
+            },
+        )? {
+            let Some(version) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<Version>().ok())
+            else {
+                continue;
+            };
+            let chromedriver_executable = self
+                .platform_dir(version)?
+                .join(platform.chromedriver_executable_path());
+            if chromedriver_executable.is_file() {
+                versions.push(version);
+            }
+        }
+        Ok(versions)
+    }
+
+    /// Describe an installed version as a [`SelectedVersion`], listing only artifacts that are
+    /// present on disk.
+    fn installed_selected_version(
+        &self,
+        version: Version,
+    ) -> Result<SelectedVersion, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let platform_dir = self.platform_dir(version)?;
+        let installed = |artifact: ChromeForTestingArtifact, executable: &Path| {
+            platform_dir
+                .join(executable)
+                .is_file()
+                .then(|| canonical_download(artifact, version, platform))
+        };
+
+        let chrome = installed(
+            ChromeForTestingArtifact::Chrome,
+            ChromeBinary::Chrome.executable_path(platform),
+        );
+        let chrome_headless_shell = installed(
+            ChromeForTestingArtifact::ChromeHeadlessShell,
+            ChromeBinary::ChromeHeadlessShell.executable_path(platform),
+        );
+        let chromedriver = installed(
+            ChromeForTestingArtifact::ChromeDriver,
+            platform.chromedriver_executable_path(),
+        );
+
+        Ok(SelectedVersion {
+            channel: None,
+            version,
+            milestone: None,
+            revision: None,
+            downloads: ArtifactDownloads::single_platform(
+                chrome.as_ref(),
+                chrome_headless_shell.as_ref(),
+                chromedriver.as_ref(),
+            ),
+            chrome,
+            chrome_headless_shell,
+            chromedriver,
+            head_of_channels: None,
+            system_chrome: None,
+            staleness: None,
+        })
+    }
+
+    /// Download the requested browser artifact(s) and matching `ChromeDriver`.
+    ///
+    /// Returns one [`LoadedBrowserPackage`] per requested [`ChromeBinary`], in request order. The
+    /// download work is de-duplicated, so repeated binary values do not download the same artifact
+    /// more than once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `chrome_binaries` is empty, no platform-matching browser or
+    /// `ChromeDriver` download exists, the cache directory cannot be prepared, the download /
+    /// extraction fails, or an artifact is missing while in offline mode.
+    pub async fn download(
+        &self,
+        selected: &SelectedVersion,
+        chrome_binaries: &[ChromeBinary],
+    ) -> Result<Vec<LoadedBrowserPackage>, Report<ChromeForTestingManagerError>> {
+        self.download_with_chromedriver(selected, selected, chrome_binaries)
+            .await
+    }
+
+    /// Download the requested browser artifact(s) of `browser` and the `ChromeDriver` of
+    /// `chromedriver`, e.g. to reproduce a driver regression with `ChromeDriver` 132 and Chrome
+    /// 131. Resolve both through [`Self::resolve_version`] with separate [`VersionRequest`]s.
+    ///
+    /// `ChromeDriver` only officially supports its own Chrome milestone. Majors one apart are
+    /// accepted with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ChromeForTestingManagerError::IncompatibleChromedriver`] if the majors differ by
+    /// more than one. Otherwise fails like [`Self::download`].
+    pub async fn download_with_chromedriver(
+        &self,
+        browser: &SelectedVersion,
+        chromedriver: &SelectedVersion,
+        chrome_binaries: &[ChromeBinary],
+    ) -> Result<Vec<LoadedBrowserPackage>, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let requested = RequestedChromeBinaries::from_slice(chrome_binaries)?;
+        check_chromedriver_compatibility(browser.version, chromedriver.version)?;
         let artifacts = self
-            .download_requested_artifacts(selected, RequestedChromeBinaries::single(chrome_binary))
+            .download_requested_artifacts(browser, chromedriver, requested)
             .await?;
-        artifacts.package_for(chrome_binary, selected.version, self.platform)
+        let mut loaded = Vec::with_capacity(chrome_binaries.len());
+        for chrome_binary in chrome_binaries {
+            loaded.push(artifacts.package_for(*chrome_binary, platform)?);
+        }
+
+        Ok(loaded)
+    }
+
+    pub(crate) async fn download_one(
+        &self,
+        browser: &SelectedVersion,
+        chromedriver: &SelectedVersion,
+        chrome_binary: ChromeBinary,
+    ) -> Result<LoadedBrowserPackage, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        check_chromedriver_compatibility(browser.version, chromedriver.version)?;
+        let artifacts = self
+            .download_requested_artifacts(
+                browser,
+                chromedriver,
+                RequestedChromeBinaries::single(chrome_binary),
+            )
+            .await?;
+        artifacts.package_for(chrome_binary, platform)
     }
 
     async fn download_requested_artifacts(
         &self,
-        selected: &SelectedVersion,
+        browser: &SelectedVersion,
+        chromedriver: &SelectedVersion,
         requested: RequestedChromeBinaries,
     ) -> Result<DownloadedBrowserArtifacts, Report<ChromeForTestingManagerError>> {
-        let platform_dir = self.ensure_platform_dir(selected.version).await?;
+        let browser_dir = self.ensure_platform_dir(browser.version).await?;
+        let chromedriver_dir = if chromedriver.version == browser.version {
+            browser_dir.clone()
+        } else {
+            self.ensure_platform_dir(chromedriver.version).await?
+        };
+
+        // Only lock directories with a missing artifact, so read-only or pre-populated caches work
+        // without write access. Installed artifacts are re-checked once the locks are held.
+        let platform = self.target_platform()?;
+        let mut locked_dirs = Vec::with_capacity(2);
+        if !self.offline {
+            let wanted = [
+                (
+                    &chromedriver_dir,
+                    ChromeForTestingArtifact::ChromeDriver,
+                    true,
+                ),
+                (
+                    &browser_dir,
+                    ChromeForTestingArtifact::Chrome,
+                    requested.chrome,
+                ),
+                (
+                    &browser_dir,
+                    ChromeForTestingArtifact::ChromeHeadlessShell,
+                    requested.chrome_headless_shell,
+                ),
+            ];
+            for (dir, artifact, is_requested) in wanted {
+                if is_requested && !is_installed(dir, artifact, platform) {
+                    locked_dirs.push(dir);
+                }
+            }
+        }
+        // Lock in a consistent order, so processes locking the same two directories cannot deadlock.
+        locked_dirs.sort();
+        locked_dirs.dedup();
+        let mut locks = Vec::with_capacity(locked_dirs.len());
+        for dir in locked_dirs {
+            locks.push(InstallLock::acquire(dir, self.install_lock_timeout).await?);
+        }
 
-        let (chromedriver, chrome, chrome_headless_shell) = tokio::try_join!(
-            self.download_chromedriver(selected, &platform_dir),
+        let (chromedriver_executable, chrome, chrome_headless_shell) = tokio::try_join!(
+            self.download_chromedriver(chromedriver, &chromedriver_dir),
             self.download_requested_browser(
-                selected,
-                &platform_dir,
+                browser,
+                &browser_dir,
                 ChromeBinary::Chrome,
                 requested.chrome,
             ),
             self.download_requested_browser(
-                selected,
-                &platform_dir,
+                browser,
+                &browser_dir,
                 ChromeBinary::ChromeHeadlessShell,
                 requested.chrome_headless_shell,
             ),
         )?;
 
         Ok(DownloadedBrowserArtifacts {
-            chromedriver,
+            chromedriver: LoadedArtifact {
+                executable: chromedriver_executable,
+                version: chromedriver.version,
+            },
+            browser_version: browser.version,
             chrome,
             chrome_headless_shell,
         })
@@ -522,11 +1630,12 @@ impl ChromeForTestingManager {
         platform_dir: &Path,
         chrome_binary: ChromeBinary,
     ) -> Result<PathBuf, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
         let selected_chrome_download = match chrome_binary {
             ChromeBinary::Chrome => selected.chrome.clone().ok_or_else(|| {
                 report!(ChromeForTestingManagerError::NoChromeDownload {
                     version: selected.version,
-                    platform: self.platform,
+                    platform,
                 })
             })?,
             ChromeBinary::ChromeHeadlessShell => {
@@ -534,14 +1643,14 @@ impl ChromeForTestingManager {
                     report!(
                         ChromeForTestingManagerError::NoChromeHeadlessShellDownload {
                             version: selected.version,
-                            platform: self.platform,
+                            platform,
                         }
                     )
                 })?
             }
         };
 
-        let chrome_executable = platform_dir.join(chrome_binary.executable_path(self.platform));
+        let chrome_executable = platform_dir.join(chrome_binary.executable_path(platform));
         self.ensure_artifact_downloaded(
             selected,
             platform_dir,
@@ -560,15 +1669,15 @@ impl ChromeForTestingManager {
         selected: &SelectedVersion,
         platform_dir: &Path,
     ) -> Result<PathBuf, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
         let Some(selected_chromedriver_download) = selected.chromedriver.clone() else {
             bail!(ChromeForTestingManagerError::NoChromedriverDownload {
                 version: selected.version,
-                platform: self.platform,
+                platform,
             });
         };
 
-        let chromedriver_executable =
-            platform_dir.join(self.platform.chromedriver_executable_path());
+        let chromedriver_executable = platform_dir.join(platform.chromedriver_executable_path());
         self.ensure_artifact_downloaded(
             selected,
             platform_dir,
@@ -595,20 +1704,152 @@ impl ChromeForTestingManager {
             .channel
             .as_ref()
             .map_or_else(String::new, ToString::to_string);
+        let is_extracted = executable.exists() && executable.is_file();
 
-        if executable.exists() && executable.is_file() {
+        if is_extracted && InstallManifest::path(platform_dir, artifact).is_file() {
             tracing::info!(
                 "{label} {} already installed at {executable:?}...",
                 selected.version
             );
+        } else if self.offline {
+            if !is_extracted {
+                bail!(ChromeForTestingManagerError::DownloadInOfflineMode {
+                    artifact,
+                    version: selected.version,
+                });
+            }
+            tracing::warn!(
+                "{label} {} at {executable:?} has no install manifest; using it as-is in offline mode.",
+                selected.version
+            );
         } else {
+            if is_extracted {
+                tracing::warn!(
+                    "{label} {} at {executable:?} has no install manifest; reinstalling it.",
+                    selected.version
+                );
+            }
             tracing::info!("Installing {channel_label} {label} {}", selected.version);
-            download::download_zip(&self.client, url, platform_dir, platform_dir, artifact).await?;
+            self.install_artifact(selected.version, platform_dir, artifact, url, false)
+                .await?;
         }
 
         Ok(())
     }
 
+    /// Retrieve and extract `artifact` into `platform_dir`, replacing any previous installation,
+    /// and record its install manifest.
+    ///
+    /// Unless `reinstall` is set, concurrent calls for the same artifact, directory and source
+    /// within this process share a single installation, which is skipped if the artifact was
+    /// installed meanwhile, e.g. by a call that completed just before.
+    async fn install_artifact(
+        &self,
+        version: Version,
+        platform_dir: &Path,
+        artifact: ChromeForTestingArtifact,
+        url: &str,
+        reinstall: bool,
+    ) -> Result<(), Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let request = ArtifactRequest {
+            artifact,
+            version,
+            platform,
+            url: url.to_owned(),
+        };
+        let source: Arc<dyn ArtifactSource> = self.artifact_source.clone().unwrap_or_else(|| {
+            Arc::new(
+                HttpArtifactSource::new()
+                    .with_client(self.client.clone())
+                    .with_mirror(self.mirror.clone())
+                    .with_retry_policy(self.retry_policy.clone()),
+            )
+        });
+        if reinstall {
+            return install_from_source(
+                source.as_ref(),
+                &request,
+                platform_dir,
+                &self.retry_policy,
+            )
+            .await;
+        }
+        let location = source.location(&request);
+        let target_dir = platform_dir.to_owned();
+        let retry_policy = self.retry_policy.clone();
+        shared_install::coalesce(platform_dir, artifact, version, location, move || {
+            async move {
+                if is_installed(&target_dir, artifact, request.platform) {
+                    return Ok(());
+                }
+                install_from_source(source.as_ref(), &request, &target_dir, &retry_policy).await
+            }
+            .boxed()
+        })
+        .await
+    }
+
+    /// Re-check the artifacts of `version` installed for the platform, and reinstall broken ones.
+    ///
+    /// The size and SHA-256 checksum of every extracted file are compared against the install
+    /// manifest recorded when the artifact was installed. An artifact with any problem, including
+    /// a missing manifest, is removed and retrieved again from the URL it was installed from.
+    /// Artifacts of `version` that are not installed are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a broken artifact cannot be removed or reinstalled, e.g. in offline
+    /// mode.
+    pub async fn verify(
+        &self,
+        version: Version,
+    ) -> Result<Vec<ArtifactVerification>, Report<ChromeForTestingManagerError>> {
+        let platform = self.target_platform()?;
+        let platform_dir = self.platform_dir(version)?;
+        if !platform_dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let _lock = InstallLock::acquire(&platform_dir, self.install_lock_timeout).await?;
+        let mut verifications = Vec::new();
+        for artifact in [
+            ChromeForTestingArtifact::Chrome,
+            ChromeForTestingArtifact::ChromeHeadlessShell,
+            ChromeForTestingArtifact::ChromeDriver,
+        ] {
+            let manifest = InstallManifest::read(&InstallManifest::path(&platform_dir, artifact));
+            let executable = platform_dir.join(artifact_executable_path(artifact, platform));
+            if manifest.is_none() && !executable.exists() {
+                continue;
+            }
+
+            let problems = match &manifest {
+                Some(manifest) => manifest.verify(&platform_dir).await,
+                None => vec![IntegrityProblem::MissingManifest],
+            };
+            if !problems.is_empty() {
+                tracing::warn!(
+                    ?problems,
+                    "{artifact} {version} is broken; reinstalling it."
+                );
+                if self.offline {
+                    bail!(ChromeForTestingManagerError::DownloadInOfflineMode {
+                        artifact,
+                        version
+                    });
+                }
+                let url = manifest.map_or_else(
+                    || canonical_download(artifact, version, platform).url,
+                    |manifest| manifest.url().to_owned(),
+                );
+                self.install_artifact(version, &platform_dir, artifact, &url, true)
+                    .await?;
+            }
+            verifications.push(ArtifactVerification { artifact, problems });
+        }
+        Ok(verifications)
+    }
+
     /// Launch a chromedriver process from `loaded` on the requested port.
     ///
     /// Returns the spawned process handle, the actual bound port (relevant when
@@ -624,8 +1865,9 @@ impl ChromeForTestingManager {
     ///
     /// # Errors
     ///
-    /// Returns an error if the chromedriver binary cannot be spawned or does not report
-    /// successful startup within 10 seconds.
+    /// Returns an error if the manager targets a foreign platform (see [`Self::with_platform`]),
+    /// or the chromedriver binary cannot be spawned or does not report successful startup within
+    /// 10 seconds.
     ///
     /// # Panics
     ///
@@ -640,6 +1882,7 @@ impl ChromeForTestingManager {
         (ManagedProcessHandle, Port, DriverOutputInspectors),
         Report<ChromeForTestingManagerError>,
     > {
+        self.ensure_host_platform()?;
         let chromedriver_executable = loaded.chromedriver_executable();
         let chromedriver_exe_path_str = chromedriver_executable.to_str().expect("valid unicode");
 
@@ -754,6 +1997,7 @@ impl ChromeForTestingManager {
         caps: &thirtyfour::ChromeCapabilities,
         shutdown: GracefulShutdown,
     ) -> Result<HeadlessShellSession, Report<ChromeForTestingManagerError>> {
+        self.ensure_host_platform()?;
         let chrome_headless_shell_executable =
             loaded.chrome_headless_shell_executable().to_path_buf();
         let chrome_headless_shell_executable_str = chrome_headless_shell_executable
@@ -1073,37 +2317,64 @@ impl ChromeForTestingManager {
     }
 }
 
-fn unsupported_platform_error(err: impl std::fmt::Display) -> Report<ChromeForTestingManagerError> {
-    report!(ChromeForTestingManagerError::UnsupportedPlatform)
-        .attach(format!("chrome-for-testing error:\n{err}"))
+/// Accept a `ChromeDriver` of the browser's major, warn about one a major apart, and reject any
+/// other.
+fn check_chromedriver_compatibility(
+    browser_version: Version,
+    chromedriver_version: Version,
+) -> Result<(), Report<ChromeForTestingManagerError>> {
+    match browser_version.major.abs_diff(chromedriver_version.major) {
+        0 => Ok(()),
+        1 => {
+            tracing::warn!(
+                "ChromeDriver {chromedriver_version} is one major apart from browser {browser_version}; expect incompatibilities."
+            );
+            Ok(())
+        }
+        _ => Err(report!(
+            ChromeForTestingManagerError::IncompatibleChromedriver {
+                chromedriver_version,
+                browser_version,
+            }
+        )),
+    }
 }
 
-fn request_versions_error(
-    err: impl std::fmt::Display,
-    version_request: &VersionRequest,
+fn attach_exclusions(
+    report: Report<ChromeForTestingManagerError>,
+    exclusions: Vec<VersionExclusion>,
 ) -> Report<ChromeForTestingManagerError> {
-    report!(ChromeForTestingManagerError::RequestVersions {
-        version_request: version_request.clone(),
-    })
-    .attach(format!("chrome-for-testing error:\n{err}"))
+    if exclusions.is_empty() {
+        return report.attach("no candidates to choose from");
+    }
+    exclusions.into_iter().fold(report, Report::attach)
 }
 
 #[cfg(test)]
 mod tests {
     use crate::chromedriver::default_graceful_shutdown;
-    use crate::mgr::{ChromeBinary, ChromeForTestingManager, LoadedBrowserPackage};
+    use crate::mgr::{ChromeBinary, ChromeForTestingManager, LoadedArtifact, LoadedBrowserPackage};
     use crate::mgr::{
         DEFAULT_HEADLESS_SHELL_REMOTE_DEBUGGING_ARG, RemoteDebuggingArg,
         classify_remote_debugging_arg, headless_shell_launch_args, parse_devtools_address,
     };
     use crate::port::Port;
     use crate::port::PortRequest;
+    use crate::test_support::{self, version};
     use crate::version::SelectedVersion;
-    use crate::{Channel, Version, VersionRequest};
+    use crate::version::{ArtifactDownloads, canonical_download};
+    use crate::{
+        ArtifactRequest, ArtifactVerification, Channel, InMemoryReleaseIndexSource,
+        ReaderArtifactSource, ReleaseIndexDocument, StalenessAdvisory, StalenessCheck,
+        SystemChrome, Version, VersionRequest,
+    };
     use assertr::prelude::*;
+    use chrome_for_testing::Platform;
     use rootcause::Report;
     use serial_test::serial;
     use std::path::{Path, PathBuf};
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
     use thirtyfour::ChromiumLikeCapabilities;
 
     #[ctor::ctor(unsafe)]
@@ -1193,21 +2464,27 @@ mod tests {
 
     #[test]
     fn loaded_browser_package_preserves_browser_type_and_paths() {
+        let artifact = |executable: &str, version| LoadedArtifact {
+            executable: PathBuf::from(executable),
+            version,
+        };
         let chrome_package = LoadedBrowserPackage::new(
             ChromeBinary::Chrome,
-            PathBuf::from("/cache/chrome"),
-            PathBuf::from("/cache/chromedriver"),
+            artifact("/cache/chrome", version(131, 0, 6778, 204)),
+            artifact("/cache/chromedriver", version(132, 0, 6834, 83)),
         );
         assert_that!(chrome_package.chrome_binary()).is_equal_to(ChromeBinary::Chrome);
         assert_that!(chrome_package.browser_executable()).is_equal_to(Path::new("/cache/chrome"));
+        assert_that!(chrome_package.browser_version()).is_equal_to(version(131, 0, 6778, 204));
         assert_that!(chrome_package.chromedriver_executable())
             .is_equal_to(Path::new("/cache/chromedriver"));
+        assert_that!(chrome_package.chromedriver_version()).is_equal_to(version(132, 0, 6834, 83));
         assert_that!(matches!(chrome_package, LoadedBrowserPackage::Chrome(_))).is_true();
 
         let headless_shell_package = LoadedBrowserPackage::new(
             ChromeBinary::ChromeHeadlessShell,
-            PathBuf::from("/cache/chrome-headless-shell"),
-            PathBuf::from("/cache/chromedriver"),
+            artifact("/cache/chrome-headless-shell", version(131, 0, 6778, 204)),
+            artifact("/cache/chromedriver", version(131, 0, 6778, 204)),
         );
         assert_that!(headless_shell_package.chrome_binary())
             .is_equal_to(ChromeBinary::ChromeHeadlessShell);
@@ -1419,6 +2696,1054 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_resolution_picks_installed_versions() -> Result<(), Report> {
+        let cache_dir = tempfile::tempdir()?;
+        let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+            .with_offline(true);
+        install_fake_chromedriver(&mgr, version(131, 0, 6778, 85))?;
+        install_fake_chromedriver(&mgr, version(135, 0, 7019, 0))?;
+        // A version directory without an installed chromedriver does not count as installed.
+        std::fs::create_dir_all(mgr.platform_dir(version(136, 0, 0, 0))?)?;
+
+        let latest = mgr.resolve_version(VersionRequest::stable()).await?;
+        assert_that!(latest.version()).is_equal_to(version(135, 0, 7019, 0));
+        assert_that!(latest.has_chromedriver_download()).is_true();
+        assert_that!(latest.has_chrome_download()).is_false();
+
+        let fixed = mgr
+            .resolve_version(VersionRequest::Fixed(version(131, 0, 6778, 85)))
+            .await?;
+        assert_that!(fixed.version()).is_equal_to(version(131, 0, 6778, 85));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_resolution_reports_missing_installation() -> Result<(), Report> {
+        let cache_dir = tempfile::tempdir()?;
+        let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+            .with_offline(true);
+        install_fake_chromedriver(&mgr, version(131, 0, 6778, 85))?;
+
+        assert_that!(
+            mgr.resolve_version(VersionRequest::Fixed(version(135, 0, 7019, 0)))
+                .await
+        )
+        .is_err()
+        .derive(ToString::to_string)
+        .contains("(offline mode)");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_download_refuses_missing_artifacts() -> Result<(), Report> {
+        let cache_dir = tempfile::tempdir()?;
+        let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+            .with_offline(true);
+        let mut selected = selected_without_downloads();
+        selected.chromedriver = Some(crate::version::canonical_download(
+            crate::ChromeForTestingArtifact::ChromeDriver,
+            selected.version,
+            mgr.target_platform()?,
+        ));
+
+        assert_that!(mgr.download(&selected, &[ChromeBinary::Chrome]).await)
+            .is_err()
+            .derive(ToString::to_string)
+            .contains("cannot be downloaded in offline mode");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fixed_version_falls_back_to_canonical_downloads_without_release_index()
+    -> Result<(), Report> {
+        // Answers every request with `501 Not Implemented`.
+        let server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let mgr = mgr
+            .with_platform(Platform::Linux64)
+            .with_retry_policy(crate::RetryPolicy::none());
+        let v = version(135, 0, 7019, 0);
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+
+        assert_that!(selected.version()).is_equal_to(v);
+        assert_that!(selected.chromedriver.as_ref().map(|d| d.url.clone())).is_equal_to(Some(
+            canonical_download(
+                crate::ChromeForTestingArtifact::ChromeDriver,
+                v,
+                Platform::Linux64,
+            )
+            .url,
+        ));
+        assert_that!(mgr.resolve_version(VersionRequest::Milestone(135)).await).is_err();
+        Ok(())
+    }
+
+    #[cfg(feature = "embedded-index")]
+    #[tokio::test]
+    async fn resolves_from_embedded_snapshot_without_network_or_cache() -> Result<(), Report> {
+        // Answers every request with `501 Not Implemented`.
+        let server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+
+        let selected = mgr
+            .resolve_version(VersionRequest::Fixed(version(147, 0, 7727, 56)))
+            .await?;
+
+        assert_that!(selected.version()).is_equal_to(version(147, 0, 7727, 56));
+        assert_that!(selected.has_chromedriver_download()).is_true();
+        assert_that!(selected.head_of_channels()).is_none();
+
+        let selected = mgr.resolve_version(VersionRequest::stable()).await?;
+        assert_that!(selected.channel()).is_equal_to(Some(&Channel::Stable));
+        assert_that!(selected.has_chromedriver_download()).is_true();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resolves_against_a_custom_release_index_source() -> Result<(), Report> {
+        let cache_dir = tempfile::tempdir()?;
+        let platform = Platform::Linux64;
+        let (old, new) = (version(134, 0, 6998, 35), version(135, 0, 7049, 42));
+        let source = InMemoryReleaseIndexSource::new()
+            .with_document(
+                ReleaseIndexDocument::KnownGoodVersions,
+                test_support::known_good_versions_json(&[old, new], platform),
+            )
+            .with_document(
+                ReleaseIndexDocument::LastKnownGoodVersions,
+                test_support::last_known_good_versions_json(&[(Channel::Stable, old)], platform),
+            );
+        let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+            .with_platform(platform)
+            .with_release_index_source(source);
+
+        let selected = mgr.resolve_version(VersionRequest::stable()).await?;
+        assert_that!(selected.version()).is_equal_to(old);
+        assert_that!(selected.channel()).is_equal_to(Some(&Channel::Stable));
+
+        let selected = mgr.resolve_version(VersionRequest::Latest).await?;
+        assert_that!(selected.version()).is_equal_to(new);
+
+        let err = mgr
+            .resolve_version(VersionRequest::Milestone(135))
+            .await
+            .expect_err("document was not provided");
+        assert_that!(format!("{err:?}"))
+            .contains("memory:latest-versions-per-milestone-with-downloads.json");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn staleness_check_compares_pinned_version_against_stable() -> Result<(), Report> {
+        let cache_dir = tempfile::tempdir()?;
+        let platform = Platform::Linux64;
+        let (pinned, stable) = (version(132, 0, 6834, 159), version(135, 0, 7049, 84));
+        let source = InMemoryReleaseIndexSource::new()
+            .with_document(
+                ReleaseIndexDocument::KnownGoodVersions,
+                test_support::known_good_versions_json(&[pinned, stable], platform),
+            )
+            .with_document(
+                ReleaseIndexDocument::LastKnownGoodVersions,
+                test_support::last_known_good_versions_json(&[(Channel::Stable, stable)], platform),
+            );
+        let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+            .with_platform(platform)
+            .with_release_index_source(source);
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(pinned)).await?;
+        assert_that!(selected.staleness()).is_none();
+
+        let mgr = mgr.with_staleness_check(StalenessCheck::max_milestones_behind(2));
+        let selected = mgr.resolve_version(VersionRequest::Fixed(pinned)).await?;
+        let advisory = selected.staleness().expect("staleness check is enabled");
+        assert_that!(advisory.stable()).is_equal_to(stable);
+        assert_that!(advisory.milestones_behind()).is_equal_to(3);
+        assert_that!(advisory.is_stale()).is_true();
+
+        let selected = mgr.resolve_version(VersionRequest::stable()).await?;
+        assert_that!(selected.staleness().map(StalenessAdvisory::is_stale))
+            .is_equal_to(Some(false));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn installs_artifacts_from_a_custom_artifact_source() -> Result<(), Report> {
+        let platform = Platform::Linux64;
+        let v = version(135, 0, 7049, 42);
+        let (_cache_dir, mgr, fetches) = test_support::fixture_manager(platform, &[v])?;
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+        let loaded = download_regular_chrome(&mgr, selected).await?;
+
+        assert_that!(loaded.browser_executable())
+            .exists()
+            .is_a_file();
+        assert_that!(loaded.chromedriver_executable())
+            .exists()
+            .is_a_file();
+        assert_that!(fetches.load(Ordering::SeqCst)).is_equal_to(2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failed_extraction_leaves_neither_partial_install_nor_staging_dir() -> Result<(), Report>
+    {
+        let platform = Platform::Linux64;
+        let v = version(135, 0, 7049, 42);
+        let (_cache_dir, mgr, _fetches) = test_support::fixture_manager(platform, &[v])?;
+        let mgr =
+            mgr.with_artifact_source(ReaderArtifactSource::new(|_: &ArtifactRequest| async {
+                Ok(std::io::Cursor::new(b"not a zip archive".to_vec()))
+            }));
+        let platform_dir = mgr.platform_dir(v)?;
+        std::fs::create_dir_all(&platform_dir)?;
+        let leftover = platform_dir.join(format!(
+            ".chrome.staging-{}-1",
+            std::process::id().wrapping_add(1)
+        ));
+        std::fs::create_dir(&leftover)?;
+        std::fs::write(leftover.join("chrome.zip"), b"partial")?;
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+        let err = download_regular_chrome(&mgr, selected)
+            .await
+            .expect_err("the archive is invalid");
+
+        assert_that!(err.to_string()).contains("is not a valid ZIP archive");
+        let remaining = std::fs::read_dir(&platform_dir)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<Result<Vec<_>, _>>()?;
+        assert_that!(remaining).is_equal_to(vec![std::ffi::OsString::from(".install.lock")]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn installed_artifacts_are_loaded_without_taking_the_install_lock() -> Result<(), Report>
+    {
+        let platform = Platform::Linux64;
+        let v = version(135, 0, 7049, 42);
+        let (_cache_dir, mgr, fetches) = test_support::fixture_manager(platform, &[v])?;
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+        mgr.download(&selected, &[ChromeBinary::Chrome]).await?;
+        let lock_file = mgr.platform_dir(v)?.join(".install.lock");
+        std::fs::remove_file(&lock_file)?;
+
+        mgr.download(&selected, &[ChromeBinary::Chrome]).await?;
+        assert_that!(lock_file.exists()).is_false();
+
+        // Artifacts without an install manifest are used as-is in offline mode.
+        std::fs::remove_file(crate::install_manifest::InstallManifest::path(
+            &mgr.platform_dir(v)?,
+            crate::ChromeForTestingArtifact::Chrome,
+        ))?;
+        let mgr = mgr.with_offline(true);
+        mgr.download(&selected, &[ChromeBinary::Chrome]).await?;
+        assert_that!(lock_file.exists()).is_false();
+        assert_that!(fetches.load(Ordering::SeqCst)).is_equal_to(2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_downloads_share_one_installation_per_artifact() -> Result<(), Report> {
+        let platform = Platform::Linux64;
+        let v = version(135, 0, 7049, 42);
+        let (_cache_dir, mgr, fetches) = test_support::fixture_manager(platform, &[v])?;
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+
+        let (first, second) = tokio::join!(
+            mgr.download(&selected, &[ChromeBinary::Chrome]),
+            mgr.download(&selected, &[ChromeBinary::Chrome]),
+        );
+
+        assert_that!(first?[0].browser_executable()).is_a_file();
+        assert_that!(second?[0].browser_executable()).is_a_file();
+        assert_that!(fetches.load(Ordering::SeqCst)).is_equal_to(2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn verify_reinstalls_artifacts_with_modified_files() -> Result<(), Report> {
+        let platform = Platform::Linux64;
+        let v = version(135, 0, 7049, 42);
+        let (_cache_dir, mgr, fetches) = test_support::fixture_manager(platform, &[v])?;
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+        let loaded = download_regular_chrome(&mgr, selected).await?;
+        assert_that!(fetches.load(Ordering::SeqCst)).is_equal_to(2);
+
+        let verifications = mgr.verify(v).await?;
+        assert_that!(verifications.iter().all(ArtifactVerification::is_intact)).is_true();
+
+        std::fs::write(loaded.chromedriver_executable(), b"#!/bin/sh\nexit 1\n")?;
+        let verifications = mgr.verify(v).await?;
+
+        assert_that!(fetches.load(Ordering::SeqCst)).is_equal_to(3);
+        let broken = verifications
+            .iter()
+            .filter(|verification| !verification.is_intact())
+            .collect::<Vec<_>>();
+        assert_that!(broken.len()).is_equal_to(1);
+        assert_that!(broken[0].artifact())
+            .is_equal_to(crate::ChromeForTestingArtifact::ChromeDriver);
+        assert_that!(matches!(
+            broken[0].problems(),
+            [crate::IntegrityProblem::SizeMismatch { .. }]
+        ))
+        .is_true();
+        assert_that!(std::fs::read(loaded.chromedriver_executable())?)
+            .is_equal_to(b"#!/bin/sh\n".to_vec());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn mirror_serves_release_index_and_artifacts() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let platform = mgr.target_platform()?;
+        let v = version(135, 0, 7019, 0);
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[v], platform),
+        )
+        .await;
+        let chrome = test_support::mock_artifact(
+            &mut server,
+            crate::ChromeForTestingArtifact::Chrome,
+            v,
+            platform,
+            platform.chrome_executable_path(),
+        )
+        .await;
+        let chromedriver = test_support::mock_artifact(
+            &mut server,
+            crate::ChromeForTestingArtifact::ChromeDriver,
+            v,
+            platform,
+            platform.chromedriver_executable_path(),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+        let loaded = download_regular_chrome(&mgr, selected).await?;
+
+        chrome.assert_async().await;
+        chromedriver.assert_async().await;
+        assert_that!(loaded.browser_executable())
+            .exists()
+            .is_a_file();
+        assert_that!(loaded.chromedriver_executable())
+            .exists()
+            .is_a_file();
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn downloads_chromedriver_of_a_different_version_than_the_browser() -> Result<(), Report>
+    {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let platform = mgr.target_platform()?;
+        let browser_version = version(131, 0, 6778, 204);
+        let chromedriver_version = version(132, 0, 6834, 83);
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(
+                &[browser_version, chromedriver_version],
+                platform,
+            ),
+        )
+        .await;
+        let chrome = test_support::mock_artifact(
+            &mut server,
+            crate::ChromeForTestingArtifact::Chrome,
+            browser_version,
+            platform,
+            platform.chrome_executable_path(),
+        )
+        .await;
+        let chromedriver = test_support::mock_artifact(
+            &mut server,
+            crate::ChromeForTestingArtifact::ChromeDriver,
+            chromedriver_version,
+            platform,
+            platform.chromedriver_executable_path(),
+        )
+        .await;
+
+        let browser = mgr.resolve_version(browser_version.into()).await?;
+        let driver = mgr.resolve_version(chromedriver_version.into()).await?;
+        let loaded = mgr
+            .download_with_chromedriver(&browser, &driver, &[ChromeBinary::Chrome])
+            .await?;
+
+        chrome.assert_async().await;
+        chromedriver.assert_async().await;
+        let loaded = &loaded[0];
+        assert_that!(loaded.browser_version()).is_equal_to(browser_version);
+        assert_that!(loaded.chromedriver_version()).is_equal_to(chromedriver_version);
+        assert_that!(loaded.browser_executable()).is_equal_to(
+            mgr.platform_dir(browser_version)?
+                .join(platform.chrome_executable_path())
+                .as_path(),
+        );
+        assert_that!(loaded.chromedriver_executable())
+            .exists()
+            .is_a_file();
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn rejects_chromedriver_more_than_one_major_apart() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let chromedriver = server
+            .mock("GET", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        let mut browser = selected_without_downloads();
+        browser.version = version(131, 0, 6778, 204);
+        let mut driver = selected_without_downloads();
+        driver.version = version(133, 0, 6943, 53);
+
+        let err = mgr
+            .download_with_chromedriver(&browser, &driver, &[ChromeBinary::Chrome])
+            .await
+            .unwrap_err();
+
+        assert_that!(matches!(
+            err.current_context(),
+            crate::ChromeForTestingManagerError::IncompatibleChromedriver { .. }
+        ))
+        .is_true();
+        chromedriver.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn resolution_exposes_release_index_metadata() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let mgr = mgr.with_channel_heads(true);
+        let stable = version(135, 0, 7049, 95);
+        let older = version(134, 0, 6998, 165);
+        let _known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[older, stable], mgr.target_platform()?),
+        )
+        .await;
+        let _last_known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LastKnownGoodVersions,
+            test_support::last_known_good_versions_json(
+                &[(Channel::Stable, stable), (Channel::Beta, stable)],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(stable)).await?;
+        assert_that!(selected.revision()).is_equal_to(Some("1"));
+        assert_that!(selected.milestone()).is_none();
+        assert_that!(selected.head_of_channels().map(<[Channel]>::to_vec))
+            .is_equal_to(Some(vec![Channel::Stable, Channel::Beta]));
+        assert_that!(
+            selected
+                .downloads(crate::ChromeForTestingArtifact::ChromeDriver)
+                .len()
+        )
+        .is_equal_to(1);
+        assert_that!(selected.download_url(
+            crate::ChromeForTestingArtifact::Chrome,
+            mgr.target_platform()?
+        ))
+        .is_equal_to(Some(
+            canonical_download(
+                crate::ChromeForTestingArtifact::Chrome,
+                stable,
+                mgr.target_platform()?,
+            )
+            .url
+            .as_str(),
+        ));
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(older)).await?;
+        assert_that!(selected.head_of_channels().map(<[Channel]>::to_vec))
+            .is_equal_to(Some(Vec::new()));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn channel_heads_are_opt_in_and_not_looked_up_for_pinned_versions() -> Result<(), Report>
+    {
+        let mut server = mockito::Server::new_async().await;
+        let (cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let mgr = mgr.with_index_ttl(Duration::ZERO);
+        let stable = version(135, 0, 7049, 95);
+        let _known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[stable], mgr.target_platform()?),
+        )
+        .await;
+        let last_known_good = server
+            .mock(
+                "GET",
+                test_support::index_path(ReleaseIndexDocument::LastKnownGoodVersions).as_str(),
+            )
+            .with_body(test_support::last_known_good_versions_json(
+                &[(Channel::Stable, stable)],
+                mgr.target_platform()?,
+            ))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(stable)).await?;
+        assert_that!(selected.head_of_channels()).is_none();
+
+        let mgr = mgr
+            .with_channel_heads(true)
+            .with_lockfile(crate::Lockfile::new(
+                cache_dir.path().join("chrome-for-testing.lock"),
+            ));
+        let selected = mgr.resolve_version(VersionRequest::Fixed(stable)).await?;
+        assert_that!(selected.head_of_channels()).is_equal_to(Some([Channel::Stable].as_slice()));
+        let selected = mgr.resolve_version(VersionRequest::Fixed(stable)).await?;
+        assert_that!(selected.head_of_channels()).is_none();
+
+        last_known_good.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn milestone_resolves_newest_build_of_major() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let v = version(131, 0, 6778, 204);
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LatestVersionsPerMilestone,
+            test_support::latest_versions_per_milestone_json(
+                &[version(130, 0, 6723, 116), v],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Milestone(131)).await?;
+
+        assert_that!(selected.version()).is_equal_to(v);
+        assert_that!(selected.milestone()).is_equal_to(Some(131));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn milestone_falls_back_to_known_good_versions() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let _per_milestone = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LatestVersionsPerMilestone,
+            test_support::latest_versions_per_milestone_json(
+                &[version(130, 0, 6723, 116)],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+        let _known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(
+                &[
+                    version(129, 0, 6668, 100),
+                    version(129, 0, 6668, 58),
+                    version(130, 0, 6723, 116),
+                ],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Milestone(129)).await?;
+        assert_that!(selected.version()).is_equal_to(version(129, 0, 6668, 100));
+
+        let err = mgr
+            .resolve_version(VersionRequest::Milestone(128))
+            .await
+            .unwrap_err();
+        assert_that!(err.to_string()).contains("Milestone(128)");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn system_chrome_resolves_closest_build_of_its_milestone() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let _known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(
+                &[
+                    version(131, 0, 6778, 69),
+                    version(131, 0, 6778, 85),
+                    version(131, 0, 6778, 204),
+                    version(132, 0, 6834, 83),
+                ],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+        let system_chrome = |version| SystemChrome {
+            executable: PathBuf::from("/usr/bin/google-chrome"),
+            version,
+        };
+
+        let selected = mgr
+            .resolve_system_chrome(system_chrome(version(131, 0, 6778, 139)))
+            .await?
+            .expect("a build of milestone 131");
+        assert_that!(selected.version()).is_equal_to(version(131, 0, 6778, 85));
+        assert_that!(selected.milestone()).is_equal_to(Some(131));
+        assert_that!(selected.system_chrome().map(SystemChrome::executable))
+            .is_equal_to(Some(Path::new("/usr/bin/google-chrome")));
+
+        let selected = mgr
+            .resolve_system_chrome(system_chrome(version(133, 0, 6943, 53)))
+            .await?;
+        assert_that!(selected).is_none();
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stable_minus_counts_back_from_the_stable_milestone() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let stable = version(131, 0, 6778, 204);
+        let two_behind = version(129, 0, 6668, 100);
+        let _last_known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LastKnownGoodVersions,
+            test_support::last_known_good_versions_json(
+                &[(Channel::Stable, stable)],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+        let _per_milestone = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LatestVersionsPerMilestone,
+            test_support::latest_versions_per_milestone_json(
+                &[two_behind, version(130, 0, 6723, 116), stable],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::StableMinus(2)).await?;
+        assert_that!(selected.version()).is_equal_to(two_behind);
+        assert_that!(selected.milestone()).is_equal_to(Some(129));
+
+        let selected = mgr.resolve_version(VersionRequest::StableMinus(0)).await?;
+        assert_that!(selected.version()).is_equal_to(stable);
+
+        let err = mgr
+            .resolve_version(VersionRequest::StableMinus(132))
+            .await
+            .unwrap_err();
+        assert_that!(err.to_string()).contains("StableMinus(132)");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn first_of_falls_back_to_the_next_candidate() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let foreign_platform = if mgr.target_platform()? == Platform::Win64 {
+            Platform::Linux64
+        } else {
+            Platform::Win64
+        };
+        let beta = version(136, 0, 7103, 25);
+        let installed = version(131, 0, 6778, 204);
+        install_fake_chromedriver(&mgr, installed)?;
+        let mut index = serde_json::from_str::<serde_json::Value>(
+            &test_support::last_known_good_versions_json(
+                &[(Channel::Beta, beta)],
+                mgr.target_platform()?,
+            ),
+        )?;
+        let canary = serde_json::from_str::<serde_json::Value>(
+            &test_support::last_known_good_versions_json(
+                &[(Channel::Canary, version(138, 0, 7156, 0))],
+                foreign_platform,
+            ),
+        )?;
+        index["channels"]["Canary"] = canary["channels"]["Canary"].clone();
+        let _last_known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LastKnownGoodVersions,
+            index.to_string(),
+        )
+        .await;
+
+        let selected = mgr
+            .resolve_version(VersionRequest::first_of([
+                VersionRequest::canary(),
+                VersionRequest::dev(),
+                VersionRequest::beta(),
+                VersionRequest::LatestInstalled,
+            ]))
+            .await?;
+        assert_that!(selected.version()).is_equal_to(beta);
+
+        let selected = mgr
+            .resolve_version(VersionRequest::first_of([
+                VersionRequest::canary(),
+                VersionRequest::LatestInstalled,
+            ]))
+            .await?;
+        assert_that!(selected.version()).is_equal_to(installed);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn first_of_records_each_rejection_as_child_report() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let _last_known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LastKnownGoodVersions,
+            test_support::last_known_good_versions_json(
+                &[(Channel::Stable, version(135, 0, 7049, 95))],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+
+        let err = mgr
+            .resolve_version(VersionRequest::first_of([
+                VersionRequest::canary(),
+                VersionRequest::LatestInstalled,
+            ]))
+            .await
+            .unwrap_err();
+
+        assert_that!(err.to_string()).contains("FirstOf");
+        let rejected = err
+            .children()
+            .iter()
+            .map(|child| {
+                match child.downcast_current_context::<crate::ChromeForTestingManagerError>() {
+                    Some(crate::ChromeForTestingManagerError::NoMatchingVersion {
+                        version_request,
+                    }) => Some(version_request.clone()),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+        assert_that!(rejected).is_equal_to(vec![
+            Some(VersionRequest::canary()),
+            Some(VersionRequest::LatestInstalled),
+        ]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_stable_minus_counts_back_from_newest_installed_milestone() -> Result<(), Report>
+    {
+        let cache_dir = tempfile::tempdir()?;
+        let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+            .with_offline(true);
+        install_fake_chromedriver(&mgr, version(129, 0, 6668, 58))?;
+        install_fake_chromedriver(&mgr, version(129, 0, 6668, 100))?;
+        install_fake_chromedriver(&mgr, version(131, 0, 6778, 204))?;
+
+        let selected = mgr.resolve_version(VersionRequest::StableMinus(2)).await?;
+        assert_that!(selected.version()).is_equal_to(version(129, 0, 6668, 100));
+        assert_that!(selected.milestone()).is_equal_to(Some(129));
+
+        let err = mgr
+            .resolve_version(VersionRequest::StableMinus(1))
+            .await
+            .unwrap_err();
+        assert_that!(err.to_string()).contains("no installed version");
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn offline_milestone_resolution_picks_newest_installed_build() -> Result<(), Report> {
+        let cache_dir = tempfile::tempdir()?;
+        let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+            .with_offline(true);
+        install_fake_chromedriver(&mgr, version(130, 0, 6723, 116))?;
+        install_fake_chromedriver(&mgr, version(131, 0, 6778, 85))?;
+        install_fake_chromedriver(&mgr, version(131, 0, 6778, 204))?;
+
+        let selected = mgr.resolve_version(VersionRequest::Milestone(131)).await?;
+
+        assert_that!(selected.version()).is_equal_to(version(131, 0, 6778, 204));
+        assert_that!(selected.milestone()).is_equal_to(Some(131));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unmatched_version_filter_reports_exclusions() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(
+                &[version(119, 0, 6045, 105), version(126, 0, 6478, 0)],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+
+        let err = mgr
+            .resolve_version(VersionRequest::Matching(
+                crate::VersionFilter::new().range(version(120, 0, 0, 0)..version(125, 0, 0, 0)),
+            ))
+            .await
+            .unwrap_err();
+
+        assert_that!(err.to_string()).contains("could not determine a version");
+        let attachments = err
+            .attachments()
+            .iter()
+            .map(|attachment| attachment.to_string())
+            .filter(|attachment| attachment.starts_with("constraint"))
+            .collect::<Vec<_>>();
+        assert_that!(attachments).contains_exactly([
+            "constraint `>= 120.0.0.0` excluded 1 candidate(s): 119.0.6045.105",
+            "constraint `< 125.0.0.0` excluded 1 candidate(s): 126.0.6478.0",
+        ]);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn lockfile_pins_version_until_updated() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let lockfile = crate::Lockfile::new(cache_dir.path().join("chrome-for-testing.lock"));
+        let mgr = mgr
+            .with_index_ttl(Duration::ZERO)
+            .with_lockfile(lockfile.clone());
+        let (v1, v2) = (version(135, 0, 7019, 0), version(136, 0, 7103, 0));
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[v1], mgr.target_platform()?),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Latest).await?;
+        assert_that!(selected.version()).is_equal_to(v1);
+        assert_that!(lockfile.path()).exists().is_a_file();
+
+        server.reset();
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[v1, v2], mgr.target_platform()?),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Latest).await?;
+        assert_that!(selected.version()).is_equal_to(v1);
+        assert_that!(selected.has_chromedriver_download()).is_true();
+
+        let mgr = mgr.with_lockfile(lockfile.clone().with_update(true));
+        let selected = mgr.resolve_version(VersionRequest::Latest).await?;
+        assert_that!(selected.version()).is_equal_to(v2);
+
+        let mgr = mgr.with_lockfile(lockfile);
+        let selected = mgr.resolve_version(VersionRequest::Latest).await?;
+        assert_that!(selected.version()).is_equal_to(v2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn lockfile_records_downloads_of_additional_platforms() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let lockfile_path = cache_dir.path().join("chrome-for-testing.lock");
+        let mgr = mgr.with_lockfile(crate::Lockfile::new(&lockfile_path));
+        let other_platform = if mgr.target_platform()? == Platform::Win64 {
+            Platform::Linux64
+        } else {
+            Platform::Win64
+        };
+        let v = version(135, 0, 7019, 0);
+        std::fs::write(
+            &lockfile_path,
+            format!(
+                "request = \"latest\"\nversion = \"{v}\"\n\n[downloads.{other_platform}]\nchromedriver = \"https://example.com/chromedriver.zip\"\n"
+            ),
+        )?;
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(
+                &[v, version(136, 0, 7103, 0)],
+                mgr.target_platform()?,
+            ),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Latest).await?;
+
+        assert_that!(selected.version()).is_equal_to(v);
+        let lockfile = std::fs::read_to_string(&lockfile_path)?;
+        assert_that!(lockfile.as_str())
+            .contains(format!("[downloads.{other_platform}]").as_str())
+            .contains(format!("[downloads.{}]", mgr.target_platform()?).as_str());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn lists_available_versions_from_the_release_index() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let (v1, v2) = (version(135, 0, 7049, 95), version(136, 0, 7103, 25));
+        let _known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[v1, v2], Platform::MacArm64),
+        )
+        .await;
+        let _last_known_good = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::LastKnownGoodVersions,
+            test_support::last_known_good_versions_json(
+                &[(Channel::Stable, v2)],
+                Platform::MacArm64,
+            ),
+        )
+        .await;
+
+        let available = mgr.available_versions(Platform::MacArm64).await?;
+        assert_that!(
+            available
+                .iter()
+                .map(crate::AvailableVersion::version)
+                .collect::<Vec<_>>()
+        )
+        .contains_exactly([v1, v2]);
+        assert_that!(available[1].channels()).contains_exactly([Channel::Stable]);
+        assert_that!(available[1].has_download(ChromeBinary::ChromeHeadlessShell)).is_true();
+
+        let available = mgr.available_versions(Platform::Win64).await?;
+        assert_that!(available).is_empty();
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn foreign_platform_artifacts_are_downloaded_but_not_launched() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (cache_dir, mgr) = test_support::mirrored_manager(&server)?;
+        let platform = if mgr.target_platform()? == Platform::Linux64 {
+            Platform::MacArm64
+        } else {
+            Platform::Linux64
+        };
+        let mgr = mgr.with_platform(platform);
+        let v = version(135, 0, 7019, 0);
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[v], platform),
+        )
+        .await;
+        let _chromedriver = test_support::mock_artifact(
+            &mut server,
+            crate::ChromeForTestingArtifact::ChromeDriver,
+            v,
+            platform,
+            platform.chromedriver_executable_path(),
+        )
+        .await;
+        let _headless_shell = test_support::mock_artifact(
+            &mut server,
+            crate::ChromeForTestingArtifact::ChromeHeadlessShell,
+            v,
+            platform,
+            platform.chrome_headless_shell_executable_path(),
+        )
+        .await;
+
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+        let loaded = mgr
+            .download_one(&selected, &selected, ChromeBinary::ChromeHeadlessShell)
+            .await?;
+
+        assert_that!(loaded.chromedriver_executable()).starts_with(
+            cache_dir
+                .path()
+                .join(v.to_string())
+                .join(platform.to_string()),
+        );
+        assert_that!(loaded.chromedriver_executable()).exists();
+        let err = mgr
+            .launch_chromedriver(&loaded, PortRequest::Any, None, default_graceful_shutdown())
+            .await
+            .unwrap_err();
+        assert_that!(matches!(
+            err.current_context(),
+            crate::ChromeForTestingManagerError::ForeignPlatform { platform: p, .. } if *p == platform
+        ))
+        .is_true();
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn unsupported_host_requires_an_explicit_platform() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let (_cache_dir, mut mgr) = test_support::mirrored_manager(&server)?;
+        // As if running on a host chrome-for-testing publishes no binaries for.
+        mgr.platform = None;
+        mgr.host_platform = None;
+        let v = version(135, 0, 7019, 0);
+        let _index = test_support::mock_index(
+            &mut server,
+            ReleaseIndexDocument::KnownGoodVersions,
+            test_support::known_good_versions_json(&[v], Platform::Linux64),
+        )
+        .await;
+
+        let err = mgr
+            .resolve_version(VersionRequest::Fixed(v))
+            .await
+            .expect_err("no target platform");
+        assert_that!(matches!(
+            err.current_context(),
+            crate::ChromeForTestingManagerError::UnsupportedPlatform
+        ))
+        .is_true();
+
+        let mgr = mgr.with_platform(Platform::Linux64);
+        let selected = mgr.resolve_version(VersionRequest::Fixed(v)).await?;
+        assert_that!(selected.has_chromedriver_download()).is_true();
+        Ok(())
+    }
+
+    fn install_fake_chromedriver(
+        mgr: &ChromeForTestingManager,
+        version: Version,
+    ) -> Result<(), Report> {
+        let chromedriver_executable = mgr
+            .platform_dir(version)?
+            .join(mgr.target_platform()?.chromedriver_executable_path());
+        std::fs::create_dir_all(chromedriver_executable.parent().expect("has parent"))?;
+        std::fs::write(chromedriver_executable, b"")?;
+        Ok(())
+    }
+
     fn selected_without_downloads() -> SelectedVersion {
         SelectedVersion {
             channel: None,
@@ -1428,9 +3753,15 @@ mod tests {
                 patch: 7019,
                 build: 0,
             },
+            milestone: None,
+            revision: None,
             chrome: None,
             chrome_headless_shell: None,
             chromedriver: None,
+            downloads: ArtifactDownloads::default(),
+            head_of_channels: None,
+            system_chrome: None,
+            staleness: None,
         }
     }
 
@@ -0,0 +1,145 @@
+use crate::index::API_BASE_URL;
+use crate::version::ARTIFACT_BASE_URL;
+use serde::Deserialize;
+use typed_builder::TypedBuilder;
+
+/// Alternative locations to fetch the chrome-for-testing release index and artifacts from.
+///
+/// Useful when only an internal mirror (e.g. an Artifactory remote repository) is reachable, or
+/// to point tests at a local stand-in HTTP server. Defaults to Google's hosts.
+///
+/// ```
+/// # use chrome_for_testing_manager::Mirror;
+/// let mirror = Mirror::builder()
+///     .index_base_url("https://artifactory.example.com/cft-index")
+///     .artifact_base_url("https://artifactory.example.com/cft-artifacts")
+///     .build();
+///
+/// assert_eq!(
+///     mirror.rewrite_artifact_url(
+///         "https://storage.googleapis.com/chrome-for-testing-public/135.0.7019.0/linux64/chrome-linux64.zip"
+///     ),
+///     "https://artifactory.example.com/cft-artifacts/135.0.7019.0/linux64/chrome-linux64.zip",
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, TypedBuilder)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Mirror {
+    /// Base URL serving the release-index documents under `chrome-for-testing/`, replacing
+    /// `https://googlechromelabs.github.io`.
+    #[builder(default, setter(into, strip_option))]
+    index_base_url: Option<String>,
+
+    /// Base URL replacing [`Self::upstream_artifact_prefix`] in artifact download URLs.
+    #[builder(default, setter(into, strip_option))]
+    artifact_base_url: Option<String>,
+
+    /// The prefix of artifact download URLs rewritten to [`Self::artifact_base_url`].
+    ///
+    /// Defaults to `https://storage.googleapis.com/chrome-for-testing-public`, under which the
+    /// release index lists all artifacts.
+    #[builder(default = ARTIFACT_BASE_URL.to_owned(), setter(into))]
+    #[serde(default = "default_upstream_artifact_prefix")]
+    upstream_artifact_prefix: String,
+}
+
+fn default_upstream_artifact_prefix() -> String {
+    ARTIFACT_BASE_URL.to_owned()
+}
+
+impl Default for Mirror {
+    fn default() -> Self {
+        Self::builder().build()
+    }
+}
+
+impl Mirror {
+    /// The base URL the release-index documents are fetched from.
+    #[must_use]
+    pub fn index_base_url(&self) -> &str {
+        self.index_base_url.as_deref().unwrap_or(API_BASE_URL)
+    }
+
+    /// The base URL artifact downloads are redirected to, if any.
+    #[must_use]
+    pub fn artifact_base_url(&self) -> Option<&str> {
+        self.artifact_base_url.as_deref()
+    }
+
+    /// The prefix of artifact download URLs that is rewritten.
+    #[must_use]
+    pub fn upstream_artifact_prefix(&self) -> &str {
+        &self.upstream_artifact_prefix
+    }
+
+    /// Rewrite an artifact download URL from the release index to point at this mirror.
+    ///
+    /// URLs not starting with [`Self::upstream_artifact_prefix`] are returned unchanged.
+    #[must_use]
+    pub fn rewrite_artifact_url(&self, url: &str) -> String {
+        let Some(artifact_base_url) = &self.artifact_base_url else {
+            return url.to_owned();
+        };
+        let upstream = self.upstream_artifact_prefix.trim_end_matches('/');
+        match url.strip_prefix(upstream) {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                format!("{}{rest}", artifact_base_url.trim_end_matches('/'))
+            }
+            _ => {
+                tracing::debug!("Not rewriting artifact URL {url:?} outside of {upstream:?}.");
+                url.to_owned()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+
+    const UPSTREAM_URL: &str = "https://storage.googleapis.com/chrome-for-testing-public/135.0.7019.0/linux64/chrome-linux64.zip";
+
+    #[test]
+    fn default_mirror_uses_google_hosts() {
+        let mirror = Mirror::default();
+
+        assert_that!(mirror.index_base_url()).is_equal_to("https://googlechromelabs.github.io");
+        assert_that!(mirror.rewrite_artifact_url(UPSTREAM_URL)).is_equal_to(UPSTREAM_URL);
+    }
+
+    #[test]
+    fn rewrites_artifact_urls_by_prefix() {
+        let mirror = Mirror::builder()
+            .artifact_base_url("http://localhost:8080/cft/")
+            .build();
+
+        assert_that!(mirror.rewrite_artifact_url(UPSTREAM_URL))
+            .is_equal_to("http://localhost:8080/cft/135.0.7019.0/linux64/chrome-linux64.zip");
+    }
+
+    #[test]
+    fn keeps_urls_outside_of_the_upstream_prefix() {
+        let mirror = Mirror::builder()
+            .artifact_base_url("http://localhost:8080/cft")
+            .build();
+
+        assert_that!(mirror.rewrite_artifact_url(
+            "https://storage.googleapis.com/chrome-for-testing-public-other/chrome.zip"
+        ))
+        .is_equal_to("https://storage.googleapis.com/chrome-for-testing-public-other/chrome.zip");
+    }
+
+    #[test]
+    fn custom_upstream_prefix_is_rewritten() {
+        let mirror = Mirror::builder()
+            .artifact_base_url("http://localhost:8080")
+            .upstream_artifact_prefix("https://edgedl.me.gvt1.com/edgedl/chrome/chrome-for-testing")
+            .build();
+
+        assert_that!(mirror.rewrite_artifact_url(
+            "https://edgedl.me.gvt1.com/edgedl/chrome/chrome-for-testing/115.0.5763.0/linux64/chrome-linux64.zip"
+        ))
+        .is_equal_to("http://localhost:8080/115.0.5763.0/linux64/chrome-linux64.zip");
+    }
+}
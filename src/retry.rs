@@ -0,0 +1,398 @@
+use crate::ChromeForTestingManagerError;
+use reqwest::StatusCode;
+use rootcause::Report;
+use rootcause::report_collection::ReportCollection;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::time::Duration;
+
+/// How often and how patiently failed requests are retried.
+///
+/// Applies to release-index fetches of [`crate::ChromeForTestingManager::resolve_version`] and to
+/// artifact downloads. Connection failures, timeouts, stalled downloads and responses with a
+/// retryable status are retried, waiting an exponentially growing backoff in between. Other
+/// failures, e.g. a `404 Not Found`, fail immediately.
+///
+/// When all attempts fail, the returned report lists every failed attempt as a child.
+///
+/// ```
+/// # use chrome_for_testing_manager::{ChromeForTestingManager, RetryPolicy, RetryableStatus};
+/// # use std::time::Duration;
+/// # fn example() -> chrome_for_testing_manager::Result<()> {
+/// let mgr = ChromeForTestingManager::new()?.with_retry_policy(
+///     RetryPolicy::new()
+///         .with_max_attempts(5)
+///         .with_backoff(Duration::from_secs(1), Duration::from_secs(30))
+///         .with_retryable_statuses([RetryableStatus::ServerError]),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    jitter: bool,
+    retryable_statuses: Vec<RetryableStatus>,
+}
+
+/// An HTTP response status, or class of statuses, worth retrying.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RetryableStatus {
+    /// Any `5xx` server error.
+    ServerError,
+
+    /// `429 Too Many Requests`.
+    TooManyRequests,
+
+    /// `408 Request Timeout`.
+    RequestTimeout,
+
+    /// The given status code.
+    Code(u16),
+}
+
+impl RetryableStatus {
+    fn matches(self, status: StatusCode) -> bool {
+        match self {
+            Self::ServerError => status.is_server_error(),
+            Self::TooManyRequests => status == StatusCode::TOO_MANY_REQUESTS,
+            Self::RequestTimeout => status == StatusCode::REQUEST_TIMEOUT,
+            Self::Code(code) => status.as_u16() == code,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Three attempts, backing off from 500 ms up to 10 s with jitter, retrying server errors,
+    /// `429 Too Many Requests` and `408 Request Timeout`.
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+            jitter: true,
+            retryable_statuses: vec![
+                RetryableStatus::ServerError,
+                RetryableStatus::TooManyRequests,
+                RetryableStatus::RequestTimeout,
+            ],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The default policy. See [`RetryPolicy::default`].
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A policy making a single attempt, i.e. never retrying.
+    #[must_use]
+    pub fn none() -> Self {
+        Self::default().with_max_attempts(1)
+    }
+
+    /// Make at most `max_attempts` attempts, including the first one. Values below `1` are
+    /// treated as `1`.
+    #[must_use]
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Wait `initial` before the first retry, doubling the wait for every further retry up to
+    /// `max`.
+    #[must_use]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Randomize each backoff to between half and all of its nominal duration, so that many
+    /// clients failing at once do not retry in lockstep. Enabled by default.
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Retry responses with any of `statuses`, replacing the previous set.
+    #[must_use]
+    pub fn with_retryable_statuses(
+        mut self,
+        statuses: impl IntoIterator<Item = RetryableStatus>,
+    ) -> Self {
+        self.retryable_statuses = statuses.into_iter().collect();
+        self
+    }
+
+    /// The maximum number of attempts, including the first one.
+    #[must_use]
+    pub const fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// The wait before the first retry.
+    #[must_use]
+    pub const fn initial_backoff(&self) -> Duration {
+        self.initial_backoff
+    }
+
+    /// The upper bound of the wait between attempts.
+    #[must_use]
+    pub const fn max_backoff(&self) -> Duration {
+        self.max_backoff
+    }
+
+    /// Whether backoffs are randomized.
+    #[must_use]
+    pub const fn jitter(&self) -> bool {
+        self.jitter
+    }
+
+    /// The response statuses that are retried.
+    #[must_use]
+    pub fn retryable_statuses(&self) -> &[RetryableStatus] {
+        &self.retryable_statuses
+    }
+
+    /// Run `attempt` until it succeeds, fails with an error that is not retryable, or the
+    /// attempts are exhausted.
+    ///
+    /// An error of the first attempt is returned as-is. Once retried, the final error has the
+    /// context `context()` and every failed attempt as a child.
+    pub(crate) async fn run<T, Fut>(
+        &self,
+        context: impl FnOnce() -> ChromeForTestingManagerError,
+        mut attempt: impl FnMut() -> Fut,
+    ) -> Result<T, Report<ChromeForTestingManagerError>>
+    where
+        Fut: Future<Output = Result<T, Report<ChromeForTestingManagerError>>>,
+    {
+        let mut failures = ReportCollection::new();
+        for number in 1..=self.max_attempts {
+            let err = match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            let retryable = self.is_retryable(&err);
+            if number == 1 && (!retryable || number == self.max_attempts) {
+                return Err(err);
+            }
+            let err = err.attach(format!("attempt {number} of {}", self.max_attempts));
+            if !retryable || number == self.max_attempts {
+                failures.push(err.into_cloneable());
+                break;
+            }
+
+            let backoff = self.backoff(number);
+            tracing::warn!(
+                "Attempt {number} of {} failed, retrying in {backoff:?}: {}",
+                self.max_attempts,
+                err.current_context()
+            );
+            failures.push(err.into_cloneable());
+            tokio::time::sleep(backoff).await;
+        }
+        Err(failures.context(context()))
+    }
+
+    /// The wait after failed attempt `number`.
+    fn backoff(&self, number: u32) -> Duration {
+        let nominal = self
+            .initial_backoff
+            .saturating_mul(2_u32.saturating_pow(number.saturating_sub(1)))
+            .min(self.max_backoff);
+        if self.jitter {
+            let half = nominal / 2;
+            half + half.mul_f64(random_fraction())
+        } else {
+            nominal
+        }
+    }
+
+    /// Whether `err` is a transient failure this policy retries.
+    pub(crate) fn is_retryable(&self, err: &Report<ChromeForTestingManagerError>) -> bool {
+        classify(err, |status| {
+            self.retryable_statuses
+                .iter()
+                .any(|retryable| retryable.matches(status))
+        })
+    }
+}
+
+/// Whether `err` means the server could not be reached, independent of any policy: a connection
+/// failure, a timeout, a stalled download, a server error or `429 Too Many Requests` response, or
+/// a `403 Forbidden` or `407 Proxy Authentication Required` response, as sent by proxies that block
+/// the host.
+pub(crate) fn is_unreachable(err: &Report<ChromeForTestingManagerError>) -> bool {
+    classify(err, |status| {
+        status.is_server_error()
+            || status == StatusCode::TOO_MANY_REQUESTS
+            || status == StatusCode::FORBIDDEN
+            || status == StatusCode::PROXY_AUTHENTICATION_REQUIRED
+    })
+}
+
+/// Whether any cause of `err` is a transport failure, or a response whose status is
+/// `retryable_status`.
+fn classify(
+    err: &Report<ChromeForTestingManagerError>,
+    retryable_status: impl Fn(StatusCode) -> bool,
+) -> bool {
+    err.iter_reports().any(|report| {
+        if let Some(err) = report.downcast_current_context::<reqwest::Error>() {
+            return match err.status() {
+                Some(status) => retryable_status(status),
+                None => err.is_connect() || err.is_timeout() || err.is_request() || err.is_body(),
+            };
+        }
+        matches!(
+            report.downcast_current_context::<ChromeForTestingManagerError>(),
+            Some(ChromeForTestingManagerError::DownloadStalled { .. })
+        )
+    })
+}
+
+/// A pseudo-random number in `[0, 1)`, good enough to spread out retries.
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.subsec_nanos().into()),
+    );
+    #[allow(clippy::cast_precision_loss)] // Only the top 53 bits are kept.
+    let fraction = (hasher.finish() >> 11) as f64 / (1_u64 << 53) as f64;
+    fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+    use rootcause::report;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn fast_policy() -> RetryPolicy {
+        RetryPolicy::new().with_backoff(Duration::ZERO, Duration::ZERO)
+    }
+
+    async fn status_error(server: &mockito::ServerGuard) -> Report<ChromeForTestingManagerError> {
+        let err = reqwest::get(server.url())
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .expect_err("mocked error status");
+        Report::new_sendsync(err).context(ChromeForTestingManagerError::FetchReleaseIndex {
+            location: server.url(),
+        })
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_up_to_the_maximum() {
+        let policy = RetryPolicy::new()
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(350))
+            .with_jitter(false);
+
+        assert_that!(policy.backoff(1)).is_equal_to(Duration::from_millis(100));
+        assert_that!(policy.backoff(2)).is_equal_to(Duration::from_millis(200));
+        assert_that!(policy.backoff(3)).is_equal_to(Duration::from_millis(350));
+
+        let jittered = policy.with_jitter(true).backoff(2);
+        assert_that!(jittered >= Duration::from_millis(100)).is_true();
+        assert_that!(jittered <= Duration::from_millis(200)).is_true();
+    }
+
+    #[tokio::test]
+    async fn retries_retryable_statuses_and_records_every_attempt() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let _unavailable = server
+            .mock("GET", "/")
+            .with_status(503)
+            .create_async()
+            .await;
+        let attempts = AtomicU32::new(0);
+
+        let err = fast_policy()
+            .run(
+                || ChromeForTestingManagerError::FetchReleaseIndex {
+                    location: server.url(),
+                },
+                || {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    let server = &server;
+                    async move { Err::<(), _>(status_error(server).await) }
+                },
+            )
+            .await
+            .expect_err("every attempt fails");
+
+        assert_that!(attempts.load(Ordering::SeqCst)).is_equal_to(3);
+        assert_that!(err.children().len()).is_equal_to(3);
+        assert_that!(err.to_string()).contains("attempt 3 of 3");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn does_not_retry_other_failures() -> Result<(), Report> {
+        let mut server = mockito::Server::new_async().await;
+        let _not_found = server
+            .mock("GET", "/")
+            .with_status(404)
+            .create_async()
+            .await;
+        let attempts = AtomicU32::new(0);
+
+        let err = fast_policy()
+            .run(
+                || ChromeForTestingManagerError::FetchReleaseIndex {
+                    location: server.url(),
+                },
+                || {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    let server = &server;
+                    async move { Err::<(), _>(status_error(server).await) }
+                },
+            )
+            .await
+            .expect_err("the attempt fails");
+
+        assert_that!(attempts.load(Ordering::SeqCst)).is_equal_to(1);
+        assert_that!(err.to_string()).does_not_contain("attempt 1");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn succeeds_once_an_attempt_succeeds() -> Result<(), Report> {
+        let attempts = AtomicU32::new(0);
+
+        let value = fast_policy()
+            .run(
+                || ChromeForTestingManagerError::FetchReleaseIndex {
+                    location: String::new(),
+                },
+                || {
+                    let number = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                    async move {
+                        if number < 2 {
+                            Err(report!(ChromeForTestingManagerError::DownloadStalled {
+                                artifact: crate::ChromeForTestingArtifact::Chrome,
+                                consecutive_stalls: 3,
+                                chunk_timeout: Duration::from_secs(30),
+                            }))
+                        } else {
+                            Ok(number)
+                        }
+                    }
+                },
+            )
+            .await?;
+
+        assert_that!(value).is_equal_to(2);
+        Ok(())
+    }
+}
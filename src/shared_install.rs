@@ -0,0 +1,243 @@
+use crate::{ChromeForTestingArtifact, ChromeForTestingManagerError};
+use chrome_for_testing::Version;
+use futures::FutureExt;
+use futures::future::{BoxFuture, Shared, WeakShared};
+use rootcause::markers::Cloneable;
+use rootcause::report_attachments::ReportAttachments;
+use rootcause::{Report, handlers};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+
+type SharedResult = Result<(), Report<String, Cloneable>>;
+
+type SharedFuture = Shared<BoxFuture<'static, SharedResult>>;
+
+type WeakSharedFuture = WeakShared<BoxFuture<'static, SharedResult>>;
+
+/// An installation in flight, keyed by platform directory, artifact and the location its archive
+/// is retrieved from.
+type InstallKey = (PathBuf, ChromeForTestingArtifact, String);
+
+/// The artifact installations in flight in this process.
+///
+/// Shared by all managers, so managers pointing at the same cache directory coalesce their installs
+/// as well. Entries are weak: an installation all callers stopped awaiting is dropped, cleaning up
+/// after itself, and the next call starts afresh.
+static IN_FLIGHT: LazyLock<Mutex<HashMap<InstallKey, WeakSharedFuture>>> =
+    LazyLock::new(Mutex::default);
+
+/// Run `install` for `artifact` in `platform_dir`, unless an installation of the same artifact
+/// into the same directory from the same `location` is already in flight in this process. Then,
+/// await that one instead.
+///
+/// Every caller receives the outcome of the single installation. The caller that started it
+/// receives its error unchanged. Callers that joined it receive a
+/// [`ChromeForTestingManagerError::InstallArtifact`] error, caused by the shared failure.
+pub(crate) async fn coalesce(
+    platform_dir: &Path,
+    artifact: ChromeForTestingArtifact,
+    version: Version,
+    location: String,
+    install: impl FnOnce() -> BoxFuture<'static, Result<(), Report<ChromeForTestingManagerError>>>,
+) -> Result<(), Report<ChromeForTestingManagerError>> {
+    let key = (platform_dir.to_owned(), artifact, location);
+    let own_error = Arc::new(Mutex::new(None));
+    let shared = {
+        let mut in_flight = IN_FLIGHT
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(shared) = in_flight.get(&key).and_then(WeakShared::upgrade) {
+            tracing::info!(
+                "Awaiting the {artifact} {version} installation already in progress in this process."
+            );
+            shared
+        } else {
+            let own_error = Arc::clone(&own_error);
+            let shared: SharedFuture = install()
+                .map(move |result| {
+                    result.map_err(|err| {
+                        let (shared_err, err) = split_error(err);
+                        *own_error
+                            .lock()
+                            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(err);
+                        shared_err
+                    })
+                })
+                .boxed()
+                .shared();
+            if let Some(weak) = shared.downgrade() {
+                in_flight.insert(key.clone(), weak);
+            }
+            shared
+        }
+    };
+
+    let result = shared.clone().await;
+
+    // The first caller to see the outcome retires the installation, so later calls start afresh.
+    let mut in_flight = IN_FLIGHT
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if in_flight
+        .get(&key)
+        .and_then(WeakShared::upgrade)
+        .is_none_or(|current| current.ptr_eq(&shared))
+    {
+        in_flight.remove(&key);
+    }
+    drop(in_flight);
+
+    result.map_err(|shared_err| {
+        // Only the caller that started the installation has its own error filled in.
+        let own_error = own_error
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take();
+        own_error.unwrap_or_else(|| {
+            shared_err.context(ChromeForTestingManagerError::InstallArtifact {
+                artifact,
+                version,
+                platform_dir: platform_dir.to_owned(),
+            })
+        })
+    })
+}
+
+/// Split `err` into a cloneable copy for callers joining the installation, and `err` itself,
+/// rebuilt around the same causes, for the caller that started it.
+///
+/// The copy carries the rendered context of `err` instead of the context itself, and none of its
+/// attachments, as neither can be cloned.
+fn split_error(
+    err: Report<ChromeForTestingManagerError>,
+) -> (
+    Report<String, Cloneable>,
+    Report<ChromeForTestingManagerError>,
+) {
+    let message = err.format_current_context().to_string();
+    let (context, children, attachments) = err.into_parts();
+    let shared: Report<String> = Report::from_parts_unhooked::<handlers::Display>(
+        message,
+        children.clone(),
+        ReportAttachments::new(),
+    );
+    let err = Report::from_parts_unhooked::<handlers::Error>(context, children, attachments);
+    (shared.into_cloneable(), err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::version;
+    use assertr::prelude::*;
+    use rootcause::report;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn concurrent_callers_share_one_installation_and_its_error() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let runs = Arc::new(AtomicUsize::new(0));
+        let install = || {
+            let runs = Arc::clone(&runs);
+            move || {
+                async move {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    Err(report!(ChromeForTestingManagerError::InvalidZip {
+                        path: PathBuf::from("chrome.zip"),
+                    }))
+                }
+                .boxed()
+            }
+        };
+        let v = version(135, 0, 7049, 42);
+
+        let location = || "https://example.com/chrome.zip".to_owned();
+
+        let (first, second) = tokio::join!(
+            coalesce(
+                dir.path(),
+                ChromeForTestingArtifact::Chrome,
+                v,
+                location(),
+                install()
+            ),
+            coalesce(
+                dir.path(),
+                ChromeForTestingArtifact::Chrome,
+                v,
+                location(),
+                install()
+            ),
+        );
+
+        assert_that!(runs.load(Ordering::SeqCst)).is_equal_to(1);
+        let first = first.expect_err("the shared installation failed");
+        assert_that!(matches!(
+            first.current_context(),
+            ChromeForTestingManagerError::InvalidZip { .. }
+        ))
+        .is_true();
+        let second = second.expect_err("the shared installation failed");
+        assert_that!(matches!(
+            second.current_context(),
+            ChromeForTestingManagerError::InstallArtifact { .. }
+        ))
+        .is_true();
+        assert_that!(second.to_string()).contains("is not a valid ZIP archive");
+
+        coalesce(
+            dir.path(),
+            ChromeForTestingArtifact::Chrome,
+            v,
+            location(),
+            install(),
+        )
+        .await
+        .expect_err("a later call installs afresh");
+        assert_that!(runs.load(Ordering::SeqCst)).is_equal_to(2);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn installations_from_different_locations_are_not_shared() -> Result<(), Report> {
+        let dir = tempfile::tempdir()?;
+        let runs = Arc::new(AtomicUsize::new(0));
+        let install = || {
+            let runs = Arc::clone(&runs);
+            move || {
+                async move {
+                    runs.fetch_add(1, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                    Ok(())
+                }
+                .boxed()
+            }
+        };
+        let v = version(135, 0, 7049, 42);
+
+        let (first, second) = tokio::join!(
+            coalesce(
+                dir.path(),
+                ChromeForTestingArtifact::Chrome,
+                v,
+                "https://mirror-a.example.com/chrome.zip".to_owned(),
+                install(),
+            ),
+            coalesce(
+                dir.path(),
+                ChromeForTestingArtifact::Chrome,
+                v,
+                "https://mirror-b.example.com/chrome.zip".to_owned(),
+                install(),
+            ),
+        );
+
+        first?;
+        second?;
+        assert_that!(runs.load(Ordering::SeqCst)).is_equal_to(2);
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use crate::{ChromeForTestingArtifact, ChromeForTestingManagerError};
+use rootcause::{Report, prelude::ResultExt};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::fs;
+
+/// Infix of staging directory names: `.<artifact>.staging-<pid>-<nanos>`.
+const STAGING_INFIX: &str = ".staging-";
+
+/// A temporary directory next to the installed artifacts, in which an artifact is downloaded and
+/// extracted before being moved into place.
+///
+/// Removed on drop. Directories left behind by killed processes are removed by
+/// [`remove_leftover_staging_dirs`].
+#[derive(Debug)]
+pub(crate) struct StagingDir {
+    path: PathBuf,
+}
+
+impl StagingDir {
+    /// Create a fresh staging directory for `artifact` in `platform_dir`.
+    ///
+    /// Synchronous, so that a cancelled installation cannot leave a directory behind that was
+    /// created after its guard was dropped.
+    pub(crate) fn create(
+        platform_dir: &Path,
+        artifact: ChromeForTestingArtifact,
+    ) -> Result<Self, Report<ChromeForTestingManagerError>> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos());
+        let path = platform_dir.join(format!(
+            ".{artifact}{STAGING_INFIX}{}-{nanos}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&path)
+            .context(ChromeForTestingManagerError::StageInstall { path: path.clone() })?;
+        Ok(Self { path })
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Move every top-level entry of the staging directory into `target_dir`, replacing existing
+    /// entries of the same name.
+    ///
+    /// Each entry is moved with a single rename, so an entry in `target_dir` is either the
+    /// previous or the fully extracted one, never a partial extraction.
+    pub(crate) async fn commit(
+        self,
+        target_dir: &Path,
+    ) -> Result<(), Report<ChromeForTestingManagerError>> {
+        let mut entries = fs::read_dir(&self.path).await.context(
+            ChromeForTestingManagerError::CommitInstall {
+                path: target_dir.to_owned(),
+            },
+        )?;
+        while let Some(entry) =
+            entries
+                .next_entry()
+                .await
+                .context(ChromeForTestingManagerError::CommitInstall {
+                    path: target_dir.to_owned(),
+                })?
+        {
+            let target = target_dir.join(entry.file_name());
+            remove_path(&target).await?;
+            fs::rename(entry.path(), &target)
+                .await
+                .context(ChromeForTestingManagerError::CommitInstall { path: target })?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if let Err(err) = std::fs::remove_dir_all(&self.path)
+            && err.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!("Failed to remove staging directory {:?}: {err}", self.path);
+        }
+    }
+}
+
+/// Remove the file or directory at `path`, if any.
+pub(crate) async fn remove_path(path: &Path) -> Result<(), Report<ChromeForTestingManagerError>> {
+    let removed = match fs::symlink_metadata(path).await {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(path).await,
+        Ok(_) => fs::remove_file(path).await,
+        Err(err) => Err(err),
+    };
+    match removed {
+        Ok(()) => {
+            tracing::debug!("Removed previous installation {path:?}.");
+            Ok(())
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(Report::new_sendsync(err).context(
+            ChromeForTestingManagerError::RemoveBrokenInstall {
+                path: path.to_owned(),
+            },
+        )),
+    }
+}
+
+/// Remove staging directories in `platform_dir` left behind by processes killed mid-install.
+///
+/// Must only be called while holding the [`crate::install_lock::InstallLock`] of `platform_dir`.
+/// No other process then stages an installation there, so every staging directory not created by
+/// this process is a leftover.
+///
+/// Failures are logged and otherwise ignored; a leftover directory does no harm.
+pub(crate) async fn remove_leftover_staging_dirs(platform_dir: &Path) {
+    let Ok(mut entries) = fs::read_dir(platform_dir).await else {
+        return;
+    };
+    let own_pid = std::process::id();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let is_leftover = entry
+            .file_name()
+            .to_str()
+            .and_then(staging_dir_pid)
+            .is_some_and(|pid| pid != own_pid);
+        if is_leftover {
+            tracing::info!("Removing leftover staging directory {:?}.", entry.path());
+            if let Err(err) = fs::remove_dir_all(entry.path()).await {
+                tracing::warn!(
+                    "Failed to remove leftover staging directory {:?}: {err}",
+                    entry.path()
+                );
+            }
+        }
+    }
+}
+
+/// The id of the process that created the staging directory named `name`, if it is one.
+fn staging_dir_pid(name: &str) -> Option<u32> {
+    let (_, suffix) = name.strip_prefix('.')?.split_once(STAGING_INFIX)?;
+    let (pid, _nanos) = suffix.split_once('-')?;
+    pid.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+
+    #[tokio::test]
+    async fn commit_replaces_previous_installation() -> Result<(), Report> {
+        let platform_dir = tempfile::tempdir()?;
+        let previous = platform_dir.path().join("chromedriver-linux64");
+        std::fs::create_dir(&previous)?;
+        std::fs::write(previous.join("stale"), b"")?;
+
+        let staging =
+            StagingDir::create(platform_dir.path(), ChromeForTestingArtifact::ChromeDriver)?;
+        let staging_path = staging.path().to_owned();
+        std::fs::create_dir(staging_path.join("chromedriver-linux64"))?;
+        std::fs::write(staging_path.join("chromedriver-linux64/chromedriver"), b"")?;
+        staging.commit(platform_dir.path()).await?;
+
+        assert_that!(previous.join("chromedriver")).exists();
+        assert_that!(previous.join("stale").exists()).is_false();
+        assert_that!(staging_path.exists()).is_false();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn dropped_staging_dir_is_removed() -> Result<(), Report> {
+        let platform_dir = tempfile::tempdir()?;
+        let staging = StagingDir::create(platform_dir.path(), ChromeForTestingArtifact::Chrome)?;
+        let staging_path = staging.path().to_owned();
+        std::fs::write(staging_path.join("chrome.zip"), b"partial")?;
+
+        drop(staging);
+
+        assert_that!(staging_path.exists()).is_false();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn removes_only_staging_dirs_of_other_processes() -> Result<(), Report> {
+        let platform_dir = tempfile::tempdir()?;
+        let leftover = platform_dir.path().join(format!(
+            ".chrome.staging-{}-1",
+            std::process::id().wrapping_add(1)
+        ));
+        let own = StagingDir::create(platform_dir.path(), ChromeForTestingArtifact::ChromeDriver)?;
+        let installed = platform_dir.path().join("chrome-linux64");
+        for dir in [&leftover, &installed] {
+            std::fs::create_dir(dir)?;
+        }
+
+        remove_leftover_staging_dirs(platform_dir.path()).await;
+
+        assert_that!(leftover.exists()).is_false();
+        assert_that!(own.path().exists()).is_true();
+        assert_that!(installed.exists()).is_true();
+        Ok(())
+    }
+
+    #[test]
+    fn parses_the_pid_of_staging_dir_names() {
+        assert_that!(staging_dir_pid(".chromedriver.staging-42-1776000000")).is_equal_to(Some(42));
+        assert_that!(staging_dir_pid(".chrome.staging-x-1")).is_none();
+        assert_that!(staging_dir_pid("chrome.staging-42-1")).is_none();
+        assert_that!(staging_dir_pid(".install.lock")).is_none();
+    }
+}
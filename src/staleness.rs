@@ -0,0 +1,138 @@
+use chrome_for_testing::Version;
+
+/// Opt-in check whether a resolved version has fallen behind the current stable release.
+///
+/// Enable it with [`crate::ChromeForTestingManager::with_staleness_check`]. Every
+/// [`crate::ChromeForTestingManager::resolve_version`] then compares the selected version, e.g.
+/// one pinned through [`crate::VersionRequest::Fixed`] or a [`crate::Lockfile`], against the
+/// stable release in the last-known-good index. The result is available through
+/// [`crate::SelectedVersion::staleness`], and a stale version is reported as a `tracing` warning.
+/// The check never fails the resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalenessCheck {
+    max_milestones_behind: Option<u32>,
+}
+
+impl StalenessCheck {
+    /// Consider a version stale as soon as the stable channel has moved past it.
+    #[must_use]
+    pub const fn stable_moved() -> Self {
+        Self {
+            max_milestones_behind: None,
+        }
+    }
+
+    /// Consider a version stale once it falls more than `n` milestones behind stable.
+    #[must_use]
+    pub const fn max_milestones_behind(n: u32) -> Self {
+        Self {
+            max_milestones_behind: Some(n),
+        }
+    }
+
+    /// Compare `version` against the current `stable` release.
+    pub(crate) fn check(self, version: Version, stable: Version) -> StalenessAdvisory {
+        let milestones_behind = stable.major.saturating_sub(version.major);
+        let is_stale = match self.max_milestones_behind {
+            None => stable > version,
+            Some(max) => milestones_behind > max,
+        };
+        StalenessAdvisory {
+            version,
+            stable,
+            milestones_behind,
+            is_stale,
+        }
+    }
+}
+
+/// The outcome of a [`StalenessCheck`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StalenessAdvisory {
+    version: Version,
+    stable: Version,
+    milestones_behind: u32,
+    is_stale: bool,
+}
+
+impl StalenessAdvisory {
+    /// The checked version.
+    #[must_use]
+    pub const fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The current stable release the version was compared against.
+    #[must_use]
+    pub const fn stable(&self) -> Version {
+        self.stable
+    }
+
+    /// How many milestones the version is behind stable. `0` for versions of the stable milestone
+    /// or newer.
+    #[must_use]
+    pub const fn milestones_behind(&self) -> u32 {
+        self.milestones_behind
+    }
+
+    /// Whether the stable channel has moved past the version.
+    #[must_use]
+    pub fn is_behind_stable(&self) -> bool {
+        self.stable > self.version
+    }
+
+    /// Whether the version exceeds the threshold of the [`StalenessCheck`].
+    #[must_use]
+    pub const fn is_stale(&self) -> bool {
+        self.is_stale
+    }
+
+    /// Emit a structured `tracing` warning if the version is stale.
+    pub(crate) fn warn_if_stale(&self) {
+        if self.is_stale {
+            tracing::warn!(
+                version = %self.version,
+                stable = %self.stable,
+                milestones_behind = self.milestones_behind,
+                "Chrome for Testing {} is outdated; the current stable release is {}.",
+                self.version,
+                self.stable,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::version;
+    use assertr::prelude::*;
+
+    #[test]
+    fn stable_moved_flags_any_older_version() {
+        let stable = version(135, 0, 7049, 84);
+        let check = StalenessCheck::stable_moved();
+
+        let advisory = check.check(version(135, 0, 7049, 42), stable);
+        assert_that!(advisory.is_stale()).is_true();
+        assert_that!(advisory.milestones_behind()).is_equal_to(0);
+
+        assert_that!(check.check(stable, stable).is_stale()).is_false();
+        assert_that!(check.check(version(136, 0, 7103, 0), stable).is_stale()).is_false();
+    }
+
+    #[test]
+    fn max_milestones_behind_tolerates_up_to_n_milestones() {
+        let stable = version(135, 0, 7049, 84);
+        let check = StalenessCheck::max_milestones_behind(2);
+
+        let advisory = check.check(version(133, 0, 6943, 141), stable);
+        assert_that!(advisory.milestones_behind()).is_equal_to(2);
+        assert_that!(advisory.is_behind_stable()).is_true();
+        assert_that!(advisory.is_stale()).is_false();
+
+        let advisory = check.check(version(132, 0, 6834, 159), stable);
+        assert_that!(advisory.milestones_behind()).is_equal_to(3);
+        assert_that!(advisory.is_stale()).is_true();
+    }
+}
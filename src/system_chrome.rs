@@ -0,0 +1,257 @@
+use crate::ChromeForTestingManagerError;
+use chrome_for_testing::Version;
+use rootcause::{Report, prelude::ResultExt, report};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Executable names of Chrome and Chromium distributions, looked up on `PATH` in this order.
+const EXECUTABLE_NAMES: &[&str] = &[
+    "google-chrome-stable",
+    "google-chrome",
+    "chromium",
+    "chromium-browser",
+    "chrome",
+];
+
+/// Well-known install locations of Chrome and Chromium on Linux, tried after `PATH`.
+const WELL_KNOWN_LOCATIONS: &[&str] = &[
+    "/opt/google/chrome/chrome",
+    "/usr/bin/google-chrome-stable",
+    "/usr/bin/google-chrome",
+    "/usr/bin/chromium",
+    "/usr/bin/chromium-browser",
+    "/usr/lib/chromium/chromium",
+    "/usr/lib/chromium-browser/chromium-browser",
+    "/snap/bin/chromium",
+];
+
+/// How long `--version` may take before the executable is considered unusable.
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A Chrome or Chromium installation found on the system, as used by
+/// [`crate::VersionRequest::SystemChrome`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemChrome {
+    pub(crate) executable: PathBuf,
+    pub(crate) version: Version,
+}
+
+impl SystemChrome {
+    /// Find a Chrome or Chromium executable on `PATH` or in a well-known Linux install location
+    /// and read its version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no executable is found or none of the executables found reports its
+    /// version.
+    pub async fn detect() -> Result<Self, Report<ChromeForTestingManagerError>> {
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        let locations = WELL_KNOWN_LOCATIONS
+            .iter()
+            .map(PathBuf::from)
+            .collect::<Vec<_>>();
+        Self::detect_in(&path, &locations).await
+    }
+
+    /// Like [`Self::detect`], but searching the `PATH`-formatted `path` and then `locations`.
+    ///
+    /// Candidates are tried in order until one reports its version, so a broken first hit (e.g. a
+    /// stale symlink or a wrapper script that fails) does not hide a working installation.
+    async fn detect_in(
+        path: &OsStr,
+        locations: &[PathBuf],
+    ) -> Result<Self, Report<ChromeForTestingManagerError>> {
+        let mut last_err = None;
+        for executable in find_executables(path, locations) {
+            match Self::from_executable(executable).await {
+                Ok(detected) => return Ok(detected),
+                Err(err) => {
+                    tracing::debug!("Skipping unusable system Chrome candidate: {err}");
+                    last_err = Some(err);
+                }
+            }
+        }
+        if let Some(err) = last_err {
+            return Err(err);
+        }
+        Err(
+            report!(ChromeForTestingManagerError::NoSystemChrome).attach(format!(
+                "searched PATH for {} and the locations {locations:?}",
+                EXECUTABLE_NAMES.join(", ")
+            )),
+        )
+    }
+
+    /// Read the version of the Chrome or Chromium `executable` by running it with `--version`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the executable cannot be run or does not print a version.
+    pub async fn from_executable(
+        executable: impl Into<PathBuf>,
+    ) -> Result<Self, Report<ChromeForTestingManagerError>> {
+        let executable = executable.into();
+        let output = tokio::time::timeout(
+            VERSION_TIMEOUT,
+            Command::new(&executable)
+                .arg("--version")
+                .kill_on_drop(true)
+                .output(),
+        )
+        .await
+        .context(ChromeForTestingManagerError::ReadSystemChromeVersion {
+            executable: executable.clone(),
+        })?
+        .context(ChromeForTestingManagerError::ReadSystemChromeVersion {
+            executable: executable.clone(),
+        })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let Some(version) = parse_version_output(&stdout) else {
+            return Err(
+                report!(ChromeForTestingManagerError::ReadSystemChromeVersion { executable })
+                    .attach(format!(
+                        "`--version` exited with {} and printed {:?}",
+                        output.status,
+                        stdout.trim()
+                    )),
+            );
+        };
+        tracing::info!("Detected system Chrome {version} at {executable:?}.");
+        Ok(Self {
+            executable,
+            version,
+        })
+    }
+
+    /// The executable the version was read from.
+    #[must_use]
+    pub fn executable(&self) -> &Path {
+        &self.executable
+    }
+
+    /// The version of the installation.
+    #[must_use]
+    pub fn version(&self) -> Version {
+        self.version
+    }
+}
+
+/// The existing executables, in search order: named like a Chrome distribution in a directory of
+/// `path`, then `locations`.
+fn find_executables(path: &OsStr, locations: &[PathBuf]) -> Vec<PathBuf> {
+    let dirs = std::env::split_paths(path).collect::<Vec<_>>();
+    EXECUTABLE_NAMES
+        .iter()
+        .flat_map(|name| dirs.iter().map(move |dir| dir.join(name)))
+        .chain(locations.iter().cloned())
+        .filter(|candidate| candidate.is_file())
+        .collect()
+}
+
+/// Extract the version from `--version` output like `Google Chrome 131.0.6778.85` or
+/// `Chromium 131.0.6778.85 built on Debian`.
+fn parse_version_output(output: &str) -> Option<Version> {
+    output
+        .split_whitespace()
+        .find_map(|word| word.parse::<Version>().ok())
+}
+
+/// The build closest to `target` among `versions`: `target` itself, else the newest older build,
+/// else the oldest newer build.
+pub(crate) fn closest_version(
+    target: Version,
+    versions: impl IntoIterator<Item = Version>,
+) -> Option<Version> {
+    let (older, newer): (Vec<_>, Vec<_>) = versions.into_iter().partition(|v| *v <= target);
+    older.into_iter().max().or_else(|| newer.into_iter().min())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::version;
+    use assertr::prelude::*;
+
+    #[test]
+    fn parses_version_output_of_chrome_distributions() {
+        assert_that!(parse_version_output("Google Chrome 131.0.6778.85 \n"))
+            .is_equal_to(Some(version(131, 0, 6778, 85)));
+        assert_that!(parse_version_output(
+            "Chromium 131.0.6778.85 built on Debian trixie/sid, running on Debian trixie/sid"
+        ))
+        .is_equal_to(Some(version(131, 0, 6778, 85)));
+        assert_that!(parse_version_output("chromium: command not found")).is_none();
+    }
+
+    #[test]
+    fn picks_the_closest_build() {
+        let builds = [
+            version(131, 0, 6778, 69),
+            version(131, 0, 6778, 85),
+            version(131, 0, 6778, 204),
+        ];
+
+        assert_that!(closest_version(version(131, 0, 6778, 85), builds))
+            .is_equal_to(Some(version(131, 0, 6778, 85)));
+        assert_that!(closest_version(version(131, 0, 6778, 100), builds))
+            .is_equal_to(Some(version(131, 0, 6778, 85)));
+        assert_that!(closest_version(version(131, 0, 6778, 10), builds))
+            .is_equal_to(Some(version(131, 0, 6778, 69)));
+        assert_that!(closest_version(version(131, 0, 6778, 10), [])).is_none();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn detects_chrome_on_path_before_well_known_locations() -> Result<(), Report> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir()?;
+        let fake_chrome = |name: &str, output: &str| -> std::io::Result<PathBuf> {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format!("#!/bin/sh\necho '{output}'\n"))?;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+            Ok(path)
+        };
+        std::fs::create_dir(dir.path().join("bin"))?;
+        let on_path = fake_chrome("bin/chromium", "Chromium 131.0.6778.85 snap")?;
+        let elsewhere = fake_chrome("chrome", "Google Chrome 135.0.7049.0")?;
+
+        let path = std::env::join_paths([dir.path().join("bin")])?;
+        let detected = SystemChrome::detect_in(&path, std::slice::from_ref(&elsewhere)).await?;
+        assert_that!(detected.executable()).is_equal_to(on_path.as_path());
+        assert_that!(detected.version()).is_equal_to(version(131, 0, 6778, 85));
+
+        let detected =
+            SystemChrome::detect_in(OsStr::new(""), std::slice::from_ref(&elsewhere)).await?;
+        assert_that!(detected.executable()).is_equal_to(elsewhere.as_path());
+        assert_that!(detected.version()).is_equal_to(version(135, 0, 7049, 0));
+
+        let broken = dir.path().join("bin/google-chrome");
+        std::fs::write(&broken, "#!/bin/sh\nexit 1\n")?;
+        std::fs::set_permissions(&broken, std::fs::Permissions::from_mode(0o755))?;
+        let detected = SystemChrome::detect_in(&path, std::slice::from_ref(&elsewhere)).await?;
+        assert_that!(detected.executable()).is_equal_to(on_path.as_path());
+
+        let err = SystemChrome::detect_in(OsStr::new(""), std::slice::from_ref(&broken))
+            .await
+            .expect_err("the only candidate is broken");
+        assert_that!(matches!(
+            err.current_context(),
+            ChromeForTestingManagerError::ReadSystemChromeVersion { .. }
+        ))
+        .is_true();
+
+        let err = SystemChrome::detect_in(OsStr::new(""), &[dir.path().join("missing")])
+            .await
+            .expect_err("no executable");
+        assert_that!(matches!(
+            err.current_context(),
+            ChromeForTestingManagerError::NoSystemChrome
+        ))
+        .is_true();
+        Ok(())
+    }
+}
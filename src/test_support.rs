@@ -0,0 +1,216 @@
+//! Fixtures for unit tests exercising resolution and downloads against a local stand-in for the
+//! chrome-for-testing hosts.
+
+use crate::version::canonical_download;
+use crate::{
+    ArtifactRequest, ChromeForTestingArtifact, ChromeForTestingManager, InMemoryReleaseIndexSource,
+    ReaderArtifactSource, ReleaseIndexDocument,
+};
+use chrome_for_testing::{Channel, Platform, Version};
+use rootcause::Report;
+use serde_json::{Value, json};
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tempfile::TempDir;
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+pub(crate) fn version(major: u32, minor: u32, patch: u32, build: u32) -> Version {
+    Version {
+        major,
+        minor,
+        patch,
+        build,
+    }
+}
+
+/// The `downloads` object of a release-index entry offering all artifacts on `platform`, using
+/// the upstream download URLs.
+pub(crate) fn downloads_json(version: Version, platform: Platform) -> Value {
+    let download = |artifact| {
+        let download = canonical_download(artifact, version, platform);
+        json!([{ "platform": download.platform, "url": download.url }])
+    };
+    json!({
+        "chrome": download(ChromeForTestingArtifact::Chrome),
+        "chromedriver": download(ChromeForTestingArtifact::ChromeDriver),
+        "chrome-headless-shell": download(ChromeForTestingArtifact::ChromeHeadlessShell),
+    })
+}
+
+/// A `known-good-versions-with-downloads.json` document listing `versions` on `platform`.
+pub(crate) fn known_good_versions_json(versions: &[Version], platform: Platform) -> String {
+    let versions = versions
+        .iter()
+        .map(|version| {
+            json!({
+                "version": version,
+                "revision": "1",
+                "downloads": downloads_json(*version, platform),
+            })
+        })
+        .collect::<Vec<_>>();
+    json!({ "timestamp": "2026-04-13T08:53:52.847Z", "versions": versions }).to_string()
+}
+
+/// A `last-known-good-versions-with-downloads.json` document pointing each of `channels` at its
+/// version on `platform`.
+pub(crate) fn last_known_good_versions_json(
+    channels: &[(Channel, Version)],
+    platform: Platform,
+) -> String {
+    let channels = channels
+        .iter()
+        .map(|(channel, version)| {
+            let entry = json!({
+                "channel": channel,
+                "version": version,
+                "revision": "1",
+                "downloads": downloads_json(*version, platform),
+            });
+            (channel.to_string(), entry)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    json!({ "timestamp": "2026-04-13T08:53:52.847Z", "channels": channels }).to_string()
+}
+
+/// A `latest-versions-per-milestone-with-downloads.json` document listing `versions` on
+/// `platform`, each as the newest build of its milestone.
+pub(crate) fn latest_versions_per_milestone_json(
+    versions: &[Version],
+    platform: Platform,
+) -> String {
+    let milestones = versions
+        .iter()
+        .map(|version| {
+            let milestone = version.major.to_string();
+            let entry = json!({
+                "milestone": milestone,
+                "version": version,
+                "revision": "1",
+                "downloads": downloads_json(*version, platform),
+            });
+            (milestone, entry)
+        })
+        .collect::<serde_json::Map<_, _>>();
+    json!({ "timestamp": "2026-04-13T08:53:52.847Z", "milestones": milestones }).to_string()
+}
+
+/// A ZIP archive containing a single (executable) file at `path`.
+pub(crate) fn zip_with_file(path: &str, contents: &[u8]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file(
+        path,
+        SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored)
+            .unix_permissions(0o755),
+    )
+    .expect("start zip entry");
+    zip.write_all(contents).expect("write zip entry");
+    zip.finish().expect("finish zip").into_inner()
+}
+
+/// The path at which the download of `artifact` is served by a mirror mocked with
+/// [`mock_artifact`].
+pub(crate) fn artifact_path(
+    artifact: ChromeForTestingArtifact,
+    version: Version,
+    platform: Platform,
+) -> String {
+    format!("/artifacts/{version}/{platform}/{artifact}-{platform}.zip")
+}
+
+/// Serve a ZIP archive containing `executable` for `artifact` from `server`.
+pub(crate) async fn mock_artifact(
+    server: &mut mockito::ServerGuard,
+    artifact: ChromeForTestingArtifact,
+    version: Version,
+    platform: Platform,
+    executable: &std::path::Path,
+) -> mockito::Mock {
+    server
+        .mock("GET", artifact_path(artifact, version, platform).as_str())
+        .with_body(zip_with_file(
+            executable.to_str().expect("valid unicode"),
+            b"#!/bin/sh\n",
+        ))
+        .create_async()
+        .await
+}
+
+/// The path at which `document` is served by a mirror created with [`mirror_for`].
+pub(crate) fn index_path(document: ReleaseIndexDocument) -> String {
+    format!("/chrome-for-testing/{}", document.file_name())
+}
+
+/// Serve `body` as `document` from `server`.
+pub(crate) async fn mock_index(
+    server: &mut mockito::ServerGuard,
+    document: ReleaseIndexDocument,
+    body: impl AsRef<[u8]>,
+) -> mockito::Mock {
+    server
+        .mock("GET", index_path(document).as_str())
+        .with_body(body)
+        .create_async()
+        .await
+}
+
+/// A mirror pointing both the release index and artifact downloads at `server`.
+pub(crate) fn mirror_for(server: &mockito::ServerGuard) -> crate::Mirror {
+    crate::Mirror::builder()
+        .index_base_url(server.url())
+        .artifact_base_url(format!("{}/artifacts", server.url()))
+        .build()
+}
+
+/// A manager for `platform` with its own cache directory, resolving `versions` from an in-memory
+/// known-good-versions index and downloading fixture archives that contain just the executable of
+/// each artifact.
+///
+/// Also returns the number of archives fetched so far. Each fetch takes a moment, so that
+/// concurrent downloads overlap.
+pub(crate) fn fixture_manager(
+    platform: Platform,
+    versions: &[Version],
+) -> Result<(TempDir, ChromeForTestingManager, Arc<AtomicUsize>), Report> {
+    let index = InMemoryReleaseIndexSource::new().with_document(
+        ReleaseIndexDocument::KnownGoodVersions,
+        known_good_versions_json(versions, platform),
+    );
+    let fetches = Arc::new(AtomicUsize::new(0));
+    let source = ReaderArtifactSource::new({
+        let fetches = Arc::clone(&fetches);
+        move |request: &ArtifactRequest| {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            let executable = match request.artifact() {
+                ChromeForTestingArtifact::ChromeDriver => platform.chromedriver_executable_path(),
+                _ => platform.chrome_executable_path(),
+            };
+            let zip = zip_with_file(executable.to_str().expect("valid unicode"), b"#!/bin/sh\n");
+            async move {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                Ok(Cursor::new(zip))
+            }
+        }
+    });
+    let cache_dir = tempfile::tempdir()?;
+    let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+        .with_platform(platform)
+        .with_release_index_source(index)
+        .with_artifact_source(source);
+    Ok((cache_dir, mgr, fetches))
+}
+
+/// A manager with a fresh cache directory, resolving versions and downloading artifacts through
+/// `server`.
+pub(crate) fn mirrored_manager(
+    server: &mockito::ServerGuard,
+) -> Result<(TempDir, ChromeForTestingManager), Report> {
+    let cache_dir = tempfile::tempdir()?;
+    let mgr = ChromeForTestingManager::new_with_cache_dir(cache_dir.path().to_owned())?
+        .with_mirror(mirror_for(server));
+    Ok((cache_dir, mgr))
+}
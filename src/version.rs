@@ -1,6 +1,15 @@
+use crate::{ChromeBinary, ChromeForTestingArtifact, StalenessAdvisory, SystemChrome};
 use chrome_for_testing::{
-    Channel, Download, Platform, Version, VersionInChannel, VersionWithoutChannel,
+    Channel, Download, KnownGoodVersions, LastKnownGoodVersions, Platform, Version,
+    VersionInChannel, VersionWithoutChannel,
 };
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Bound, RangeBounds};
+use std::sync::Arc;
+
+/// Base URL under which Google publishes all chrome-for-testing artifacts.
+pub(crate) const ARTIFACT_BASE_URL: &str =
+    "https://storage.googleapis.com/chrome-for-testing-public";
 
 /// How to pick which Chrome / `ChromeDriver` version to install and run.
 ///
@@ -19,6 +28,36 @@ pub enum VersionRequest {
 
     /// Pin a specific version to use.
     Fixed(Version),
+
+    /// Use the newest build of the given Chrome milestone (major version), e.g. `131`, that has a
+    /// `ChromeDriver` download for the platform.
+    Milestone(u32),
+
+    /// Use the newest build of the milestone `n` majors before the current stable release, e.g.
+    /// `StableMinus(2)` resolves to milestone 129 while stable is at 131. `StableMinus(0)` picks
+    /// the newest build of the stable milestone, which may be newer than the stable release.
+    StableMinus(u32),
+
+    /// Use the build of the Chrome installed on this machine's milestone that is closest to its
+    /// version. The installation is looked up on `PATH` and in well-known Linux install locations,
+    /// see [`crate::SystemChrome::detect`].
+    SystemChrome,
+
+    /// Use the newest known-good version satisfying all constraints of the [`VersionFilter`],
+    /// e.g. a version range or the availability of a Chrome Headless Shell download.
+    Matching(VersionFilter),
+
+    /// Use the newest version already installed in the cache directory, without consulting the
+    /// release index.
+    LatestInstalled,
+
+    /// Try each request in order and use the first one resolving to a version with a
+    /// `ChromeDriver` download for the platform, e.g. canary, then beta, then
+    /// [`VersionRequest::LatestInstalled`]. See [`Self::first_of`].
+    ///
+    /// Should all of them fail, the reason each one was rejected is recorded as a child of the
+    /// returned report.
+    FirstOf(Vec<VersionRequest>),
 }
 
 impl From<Channel> for VersionRequest {
@@ -33,6 +72,12 @@ impl From<Version> for VersionRequest {
     }
 }
 
+impl From<VersionFilter> for VersionRequest {
+    fn from(filter: VersionFilter) -> Self {
+        Self::Matching(filter)
+    }
+}
+
 impl VersionRequest {
     /// Latest release from the [`Channel::Stable`] channel.
     #[must_use]
@@ -57,6 +102,237 @@ impl VersionRequest {
     pub fn canary() -> Self {
         Self::LatestIn(Channel::Canary)
     }
+
+    /// Try each of `requests` in order, see [`Self::FirstOf`].
+    ///
+    /// ```
+    /// # use chrome_for_testing_manager::VersionRequest;
+    /// let request = VersionRequest::first_of([
+    ///     VersionRequest::canary(),
+    ///     VersionRequest::beta(),
+    ///     VersionRequest::LatestInstalled,
+    /// ]);
+    /// ```
+    #[must_use]
+    pub fn first_of(requests: impl IntoIterator<Item = impl Into<VersionRequest>>) -> Self {
+        Self::FirstOf(requests.into_iter().map(Into::into).collect())
+    }
+}
+
+/// Constraints a version must satisfy to be picked by [`VersionRequest::Matching`].
+///
+/// Candidates always need a `ChromeDriver` download for the platform. Of all candidates satisfying
+/// every constraint, the newest one is picked.
+///
+/// ```
+/// # use chrome_for_testing_manager::{ChromeBinary, Version, VersionFilter, VersionRequest};
+/// let from: Version = "120.0.6099.0".parse().unwrap();
+/// let to: Version = "125.0.0.0".parse().unwrap();
+///
+/// let request = VersionRequest::from(
+///     VersionFilter::new()
+///         .range(from..to)
+///         .requires(ChromeBinary::ChromeHeadlessShell)
+///         .matching(|candidate| candidate.version().build % 2 == 0),
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionFilter {
+    lower: Bound<Version>,
+    upper: Bound<Version>,
+    required_binaries: Vec<ChromeBinary>,
+    predicate: Option<VersionPredicate>,
+}
+
+impl Default for VersionFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VersionFilter {
+    /// A filter accepting every version with a `ChromeDriver` download for the platform.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            lower: Bound::Unbounded,
+            upper: Bound::Unbounded,
+            required_binaries: Vec::new(),
+            predicate: None,
+        }
+    }
+
+    /// Only accept versions within `range`, e.g. `from..to` or `from..`.
+    #[must_use]
+    pub fn range(mut self, range: impl RangeBounds<Version>) -> Self {
+        self.lower = range.start_bound().cloned();
+        self.upper = range.end_bound().cloned();
+        self
+    }
+
+    /// Only accept versions offering a download of `binary` for the platform.
+    #[must_use]
+    pub fn requires(mut self, binary: ChromeBinary) -> Self {
+        if !self.required_binaries.contains(&binary) {
+            self.required_binaries.push(binary);
+        }
+        self
+    }
+
+    /// Only accept versions for which `predicate` returns `true`.
+    ///
+    /// The predicate only sees candidates satisfying all other constraints. Replaces any
+    /// previously set predicate.
+    #[must_use]
+    pub fn matching(
+        mut self,
+        predicate: impl Fn(&SelectedVersion) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.predicate = Some(VersionPredicate(Arc::new(predicate)));
+        self
+    }
+
+    /// Pick the newest of `candidates` satisfying all constraints.
+    ///
+    /// Should no candidate qualify, the returned exclusions explain which constraint ruled out
+    /// which candidates. Each candidate is attributed to the first constraint it failed.
+    pub(crate) fn select(
+        &self,
+        candidates: impl IntoIterator<Item = SelectedVersion>,
+    ) -> Result<SelectedVersion, Vec<VersionExclusion>> {
+        let mut exclusions = Vec::<VersionExclusion>::new();
+        let mut best: Option<SelectedVersion> = None;
+
+        for candidate in candidates {
+            if let Some(constraint) = self.excluded_by(&candidate) {
+                match exclusions.iter_mut().find(|e| e.constraint == constraint) {
+                    Some(exclusion) => exclusion.versions.push(candidate.version),
+                    None => exclusions.push(VersionExclusion {
+                        constraint,
+                        versions: vec![candidate.version],
+                    }),
+                }
+            } else if best
+                .as_ref()
+                .is_none_or(|best| best.version < candidate.version)
+            {
+                best = Some(candidate);
+            }
+        }
+
+        best.ok_or(exclusions)
+    }
+
+    /// A stable representation of all constraints, e.g.
+    /// `>= 120.0.6099.0, < 125.0.0.0, requires chrome-headless-shell`. `None` if a predicate is
+    /// set, which cannot be represented.
+    pub(crate) fn key(&self) -> Option<String> {
+        if self.predicate.is_some() {
+            return None;
+        }
+        let mut constraints = Vec::new();
+        match self.lower {
+            Bound::Included(lower) => constraints.push(format!(">= {lower}")),
+            Bound::Excluded(lower) => constraints.push(format!("> {lower}")),
+            Bound::Unbounded => {}
+        }
+        match self.upper {
+            Bound::Included(upper) => constraints.push(format!("<= {upper}")),
+            Bound::Excluded(upper) => constraints.push(format!("< {upper}")),
+            Bound::Unbounded => {}
+        }
+        let mut binaries = self
+            .required_binaries
+            .iter()
+            .map(|binary| binary.artifact().to_string())
+            .collect::<Vec<_>>();
+        binaries.sort_unstable();
+        constraints.extend(
+            binaries
+                .into_iter()
+                .map(|binary| format!("requires {binary}")),
+        );
+        Some(constraints.join(", "))
+    }
+
+    fn excluded_by(&self, candidate: &SelectedVersion) -> Option<String> {
+        let version = candidate.version;
+        match self.lower {
+            Bound::Included(lower) if version < lower => return Some(format!(">= {lower}")),
+            Bound::Excluded(lower) if version <= lower => return Some(format!("> {lower}")),
+            _ => {}
+        }
+        match self.upper {
+            Bound::Included(upper) if version > upper => return Some(format!("<= {upper}")),
+            Bound::Excluded(upper) if version >= upper => return Some(format!("< {upper}")),
+            _ => {}
+        }
+        if !candidate.has_chromedriver_download() {
+            return Some(String::from("ChromeDriver download required"));
+        }
+        for binary in &self.required_binaries {
+            if !candidate.has_download(*binary) {
+                return Some(format!("{} download required", binary.label()));
+            }
+        }
+        if let Some(predicate) = &self.predicate
+            && !(predicate.0)(candidate)
+        {
+            return Some(String::from("custom predicate"));
+        }
+        None
+    }
+}
+
+/// A user-provided [`VersionFilter`] predicate, compared by identity.
+#[derive(Clone)]
+struct VersionPredicate(Arc<dyn Fn(&SelectedVersion) -> bool + Send + Sync + 'static>);
+
+impl fmt::Debug for VersionPredicate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("<predicate>")
+    }
+}
+
+impl PartialEq for VersionPredicate {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for VersionPredicate {}
+
+/// The candidates a single [`VersionFilter`] constraint ruled out, attached to
+/// [`crate::ChromeForTestingManagerError::NoMatchingVersion`] reports.
+#[derive(Debug)]
+pub(crate) struct VersionExclusion {
+    constraint: String,
+    versions: Vec<Version>,
+}
+
+impl Display for VersionExclusion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const LISTED: usize = 5;
+
+        let mut versions = self.versions.clone();
+        versions.sort_unstable_by(|a, b| b.cmp(a));
+        write!(
+            f,
+            "constraint `{}` excluded {} candidate(s): ",
+            self.constraint,
+            versions.len()
+        )?;
+        for (i, version) in versions.iter().take(LISTED).enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{version}")?;
+        }
+        if versions.len() > LISTED {
+            write!(f, ", ... ({} more)", versions.len() - LISTED)?;
+        }
+        Ok(())
+    }
 }
 
 /// A version of Chrome and `ChromeDriver` that has been resolved against the
@@ -68,9 +344,16 @@ impl VersionRequest {
 pub struct SelectedVersion {
     pub(crate) channel: Option<Channel>,
     pub(crate) version: Version,
+    pub(crate) milestone: Option<u32>,
+    pub(crate) revision: Option<String>,
     pub(crate) chrome: Option<Download>,
     pub(crate) chrome_headless_shell: Option<Download>,
     pub(crate) chromedriver: Option<Download>,
+    /// Downloads for all platforms listed in the release index.
+    pub(crate) downloads: ArtifactDownloads,
+    pub(crate) head_of_channels: Option<Vec<Channel>>,
+    pub(crate) system_chrome: Option<SystemChrome>,
+    pub(crate) staleness: Option<StalenessAdvisory>,
 }
 
 impl SelectedVersion {
@@ -81,29 +364,210 @@ impl SelectedVersion {
         self.channel.as_ref()
     }
 
+    /// The milestone this version was resolved through, if any.
+    ///
+    /// `None` unless resolved by [`VersionRequest::Milestone`], [`VersionRequest::StableMinus`] or
+    /// [`VersionRequest::SystemChrome`]. The milestone of any version is its major version.
+    #[must_use]
+    pub fn milestone(&self) -> Option<u32> {
+        self.milestone
+    }
+
+    /// The Chromium revision this version was built from. `None` for versions resolved offline.
+    #[must_use]
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    /// The channels whose latest release this version currently is, e.g. `[Stable]`, or an empty
+    /// slice if none.
+    ///
+    /// `None` unless enabled via [`crate::ChromeForTestingManager::with_channel_heads`], and for
+    /// versions resolved offline, reused from a [`crate::Lockfile`] or resolved while the
+    /// last-known-good index could not be fetched.
+    #[must_use]
+    pub fn head_of_channels(&self) -> Option<&[Channel]> {
+        self.head_of_channels.as_deref()
+    }
+
+    /// The downloads of `artifact` for every platform listed in the release index.
+    ///
+    /// Versions resolved offline only list the artifacts installed for the target platform.
+    /// Versions pinned by a [`crate::Lockfile`] only list the recorded platforms.
+    #[must_use]
+    pub fn downloads(&self, artifact: ChromeForTestingArtifact) -> &[Download] {
+        self.downloads.get(artifact)
+    }
+
+    /// The download URL of `artifact` for `platform`, if listed. See [`Self::downloads`].
+    #[must_use]
+    pub fn download_url(
+        &self,
+        artifact: ChromeForTestingArtifact,
+        platform: Platform,
+    ) -> Option<&str> {
+        self.downloads
+            .get(artifact)
+            .iter()
+            .find(|download| download.platform == platform)
+            .map(|download| download.url.as_str())
+    }
+
+    /// How this version compares to the current stable release. `None` unless a
+    /// [`crate::StalenessCheck`] is enabled, or if the last-known-good index could not be fetched.
+    #[must_use]
+    pub const fn staleness(&self) -> Option<&StalenessAdvisory> {
+        self.staleness.as_ref()
+    }
+
+    /// The system Chrome installation this version was matched against.
+    ///
+    /// `None` unless freshly resolved by [`VersionRequest::SystemChrome`].
+    #[must_use]
+    pub fn system_chrome(&self) -> Option<&SystemChrome> {
+        self.system_chrome.as_ref()
+    }
+
     /// The pinned [`Version`] that will be downloaded.
     #[must_use]
     pub fn version(&self) -> Version {
         self.version
     }
 
-    /// Whether a Chrome download exists for this version on the detected platform.
+    /// Whether a Chrome download exists for this version on the target platform.
     #[must_use]
     pub fn has_chrome_download(&self) -> bool {
         self.chrome.is_some()
     }
 
-    /// Whether a Chrome Headless Shell download exists for this version on the detected platform.
+    /// Whether a Chrome Headless Shell download exists for this version on the target platform.
     #[must_use]
     pub fn has_chrome_headless_shell_download(&self) -> bool {
         self.chrome_headless_shell.is_some()
     }
 
-    /// Whether a `ChromeDriver` download exists for this version on the detected platform.
+    /// Whether a `ChromeDriver` download exists for this version on the target platform.
     #[must_use]
     pub fn has_chromedriver_download(&self) -> bool {
         self.chromedriver.is_some()
     }
+
+    /// Whether a download of `binary` exists for this version on the target platform.
+    #[must_use]
+    pub fn has_download(&self, binary: ChromeBinary) -> bool {
+        match binary {
+            ChromeBinary::Chrome => self.has_chrome_download(),
+            ChromeBinary::ChromeHeadlessShell => self.has_chrome_headless_shell_download(),
+        }
+    }
+}
+
+/// A version listed in the chrome-for-testing release index, as returned by
+/// [`crate::ChromeForTestingManager::available_versions`].
+///
+/// Download availability refers to the platform the listing was requested for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AvailableVersion {
+    pub(crate) version: Version,
+    pub(crate) revision: Option<String>,
+    pub(crate) channels: Vec<Channel>,
+    pub(crate) chrome: bool,
+    pub(crate) chrome_headless_shell: bool,
+    pub(crate) chromedriver: bool,
+}
+
+impl AvailableVersion {
+    pub(crate) fn new(version: &VersionWithoutChannel, platform: Platform) -> Self {
+        Self {
+            version: version.version,
+            revision: Some(version.revision.clone()),
+            channels: Vec::new(),
+            chrome: version.downloads.chrome_for_platform(platform).is_some(),
+            chrome_headless_shell: version
+                .downloads
+                .chrome_headless_shell_for_platform(platform)
+                .is_some(),
+            chromedriver: version
+                .downloads
+                .chromedriver_for_platform(platform)
+                .is_some(),
+        }
+    }
+
+    /// The version.
+    #[must_use]
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The Chromium revision this version was built from. `None` for versions listed from the
+    /// cache in offline mode.
+    #[must_use]
+    pub fn revision(&self) -> Option<&str> {
+        self.revision.as_deref()
+    }
+
+    /// The channels whose latest release this version currently is.
+    #[must_use]
+    pub fn channels(&self) -> &[Channel] {
+        &self.channels
+    }
+
+    /// Whether a Chrome download exists for this version on the requested platform.
+    #[must_use]
+    pub fn has_chrome_download(&self) -> bool {
+        self.chrome
+    }
+
+    /// Whether a Chrome Headless Shell download exists for this version on the requested platform.
+    #[must_use]
+    pub fn has_chrome_headless_shell_download(&self) -> bool {
+        self.chrome_headless_shell
+    }
+
+    /// Whether a `ChromeDriver` download exists for this version on the requested platform.
+    #[must_use]
+    pub fn has_chromedriver_download(&self) -> bool {
+        self.chromedriver
+    }
+
+    /// Whether a download of `binary` exists for this version on the requested platform.
+    #[must_use]
+    pub fn has_download(&self, binary: ChromeBinary) -> bool {
+        match binary {
+            ChromeBinary::Chrome => self.has_chrome_download(),
+            ChromeBinary::ChromeHeadlessShell => self.has_chrome_headless_shell_download(),
+        }
+    }
+
+    fn has_any_download(&self) -> bool {
+        self.chrome || self.chrome_headless_shell || self.chromedriver
+    }
+}
+
+/// List the versions of `known_good` offering any download on `platform`, oldest first.
+///
+/// Each version is annotated with the channels whose latest release it is, according to
+/// `last_known_good`.
+pub(crate) fn available_versions(
+    known_good: &KnownGoodVersions,
+    last_known_good: &LastKnownGoodVersions,
+    platform: Platform,
+) -> Vec<AvailableVersion> {
+    let mut versions = known_good
+        .versions
+        .iter()
+        .map(|version| AvailableVersion::new(version, platform))
+        .filter(AvailableVersion::has_any_download)
+        .collect::<Vec<_>>();
+    versions.sort_by_key(|v| v.version);
+
+    for head in channel_heads(last_known_good) {
+        if let Some(available) = versions.iter_mut().find(|v| v.version == head.version) {
+            available.channels.push(head.channel.clone());
+        }
+    }
+    versions
 }
 
 impl From<(VersionWithoutChannel, Platform)> for SelectedVersion {
@@ -111,9 +575,19 @@ impl From<(VersionWithoutChannel, Platform)> for SelectedVersion {
         SelectedVersion {
             channel: None,
             version: v.version,
+            milestone: None,
+            revision: Some(v.revision),
             chrome: v.downloads.chrome_for_platform(p).cloned(),
             chrome_headless_shell: v.downloads.chrome_headless_shell_for_platform(p).cloned(),
             chromedriver: v.downloads.chromedriver_for_platform(p).cloned(),
+            downloads: ArtifactDownloads {
+                chrome: v.downloads.chrome,
+                chrome_headless_shell: v.downloads.chrome_headless_shell.unwrap_or_default(),
+                chromedriver: v.downloads.chromedriver.unwrap_or_default(),
+            },
+            head_of_channels: None,
+            system_chrome: None,
+            staleness: None,
         }
     }
 }
@@ -126,13 +600,119 @@ impl From<(VersionInChannel, Platform)> for SelectedVersion {
         SelectedVersion {
             channel: Some(v.channel),
             version: v.version,
+            milestone: None,
+            revision: Some(v.revision),
             chrome: chrome_download,
             chrome_headless_shell: v.downloads.chrome_headless_shell_for_platform(p).cloned(),
             chromedriver: chromedriver_download,
+            downloads: ArtifactDownloads {
+                chrome: v.downloads.chrome,
+                chrome_headless_shell: v.downloads.chrome_headless_shell,
+                chromedriver: v.downloads.chromedriver,
+            },
+            head_of_channels: None,
+            system_chrome: None,
+            staleness: None,
+        }
+    }
+}
+
+impl SelectedVersion {
+    /// `version`, assumed to provide every artifact for `platform` at its [`canonical_download`]
+    /// location. Used when the release index cannot be consulted.
+    pub(crate) fn canonical(version: Version, platform: Platform) -> Self {
+        let chrome = canonical_download(ChromeForTestingArtifact::Chrome, version, platform);
+        let chrome_headless_shell = canonical_download(
+            ChromeForTestingArtifact::ChromeHeadlessShell,
+            version,
+            platform,
+        );
+        let chromedriver =
+            canonical_download(ChromeForTestingArtifact::ChromeDriver, version, platform);
+        SelectedVersion {
+            channel: None,
+            version,
+            milestone: None,
+            revision: None,
+            downloads: ArtifactDownloads::single_platform(
+                Some(&chrome),
+                Some(&chrome_headless_shell),
+                Some(&chromedriver),
+            ),
+            chrome: Some(chrome),
+            chrome_headless_shell: Some(chrome_headless_shell),
+            chromedriver: Some(chromedriver),
+            head_of_channels: None,
+            system_chrome: None,
+            staleness: None,
         }
     }
 }
 
+/// The downloads of each artifact of a version, for any number of platforms.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ArtifactDownloads {
+    pub(crate) chrome: Vec<Download>,
+    pub(crate) chrome_headless_shell: Vec<Download>,
+    pub(crate) chromedriver: Vec<Download>,
+}
+
+impl ArtifactDownloads {
+    /// The given downloads, all for a single platform.
+    pub(crate) fn single_platform(
+        chrome: Option<&Download>,
+        chrome_headless_shell: Option<&Download>,
+        chromedriver: Option<&Download>,
+    ) -> Self {
+        Self {
+            chrome: chrome.into_iter().cloned().collect(),
+            chrome_headless_shell: chrome_headless_shell.into_iter().cloned().collect(),
+            chromedriver: chromedriver.into_iter().cloned().collect(),
+        }
+    }
+
+    fn get(&self, artifact: ChromeForTestingArtifact) -> &[Download] {
+        match artifact {
+            ChromeForTestingArtifact::Chrome => &self.chrome,
+            ChromeForTestingArtifact::ChromeHeadlessShell => &self.chrome_headless_shell,
+            ChromeForTestingArtifact::ChromeDriver => &self.chromedriver,
+        }
+    }
+}
+
+/// The channel heads of `last_known_good`: known channels first, in order of increasing
+/// instability, then unknown ones by name.
+pub(crate) fn channel_heads(last_known_good: &LastKnownGoodVersions) -> Vec<&VersionInChannel> {
+    let mut heads = last_known_good.channels().values().collect::<Vec<_>>();
+    heads.sort_by_key(|head| {
+        let rank = [
+            Channel::Stable,
+            Channel::Beta,
+            Channel::Dev,
+            Channel::Canary,
+        ]
+        .iter()
+        .position(|known| *known == head.channel);
+        (rank.unwrap_or(usize::MAX), head.channel.to_string())
+    });
+    heads
+}
+
+/// The download location chrome-for-testing uses for `artifact` in `version` on `platform`.
+///
+/// The release index lists these URLs explicitly, but they follow a stable pattern that lets us
+/// describe an artifact without consulting the index.
+pub(crate) fn canonical_download(
+    artifact: ChromeForTestingArtifact,
+    version: Version,
+    platform: Platform,
+) -> Download {
+    Download {
+        platform,
+        url: format!("{ARTIFACT_BASE_URL}/{version}/{platform}/{artifact}-{platform}.zip"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,4 +744,159 @@ mod tests {
             assert_that!(VersionRequest::from(v)).is_equal_to(VersionRequest::Fixed(v));
         }
     }
+
+    mod version_filter {
+        use super::*;
+        use crate::test_support::version;
+
+        fn candidate(version: Version, headless_shell: bool) -> SelectedVersion {
+            let download =
+                |artifact| Some(canonical_download(artifact, version, Platform::Linux64));
+            SelectedVersion {
+                channel: None,
+                version,
+                milestone: None,
+                revision: None,
+                chrome: download(ChromeForTestingArtifact::Chrome),
+                chrome_headless_shell: headless_shell
+                    .then(|| download(ChromeForTestingArtifact::ChromeHeadlessShell))
+                    .flatten(),
+                chromedriver: download(ChromeForTestingArtifact::ChromeDriver),
+                downloads: ArtifactDownloads::default(),
+                head_of_channels: None,
+                system_chrome: None,
+                staleness: None,
+            }
+        }
+
+        fn candidates() -> Vec<SelectedVersion> {
+            vec![
+                candidate(version(119, 0, 6045, 105), true),
+                candidate(version(120, 0, 6099, 109), true),
+                candidate(version(124, 0, 6367, 91), false),
+                candidate(version(125, 0, 6422, 60), true),
+            ]
+        }
+
+        #[test]
+        fn picks_newest_version_within_range() {
+            let filter =
+                VersionFilter::new().range(version(120, 0, 6099, 0)..version(125, 0, 0, 0));
+
+            let selected = filter.select(candidates()).expect("a match");
+
+            assert_that!(selected.version()).is_equal_to(version(124, 0, 6367, 91));
+        }
+
+        #[test]
+        fn respects_required_binaries_and_predicate() {
+            let filter = VersionFilter::new()
+                .requires(ChromeBinary::ChromeHeadlessShell)
+                .matching(|candidate| candidate.version().major < 125);
+
+            let selected = filter.select(candidates()).expect("a match");
+
+            assert_that!(selected.version()).is_equal_to(version(120, 0, 6099, 109));
+        }
+
+        #[test]
+        fn explains_which_constraint_excluded_which_candidates() {
+            let filter = VersionFilter::new()
+                .range(version(120, 0, 0, 0)..=version(124, 0, 6367, 91))
+                .requires(ChromeBinary::ChromeHeadlessShell)
+                .matching(|_| false);
+
+            let exclusions = filter
+                .select(candidates())
+                .expect_err("no match")
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>();
+
+            assert_that!(exclusions).contains_exactly([
+                "constraint `>= 120.0.0.0` excluded 1 candidate(s): 119.0.6045.105",
+                "constraint `custom predicate` excluded 1 candidate(s): 120.0.6099.109",
+                "constraint `Chrome Headless Shell download required` excluded 1 candidate(s): 124.0.6367.91",
+                "constraint `<= 124.0.6367.91` excluded 1 candidate(s): 125.0.6422.60",
+            ]);
+        }
+
+        #[test]
+        fn filters_are_compared_by_predicate_identity() {
+            let filter = VersionFilter::new().matching(|_| true);
+
+            assert_that!(filter.clone()).is_equal_to(filter.clone());
+            assert_that!(filter).is_not_equal_to(VersionFilter::new().matching(|_| true));
+        }
+    }
+
+    mod available_versions {
+        use super::*;
+        use crate::test_support::{self, version};
+
+        #[test]
+        fn lists_versions_with_downloads_for_the_platform_and_their_channels() {
+            let (old, stable, beta) = (
+                version(120, 0, 6099, 109),
+                version(135, 0, 7049, 95),
+                version(136, 0, 7103, 25),
+            );
+            let mut known_good: serde_json::Value = serde_json::from_str(
+                &test_support::known_good_versions_json(&[beta, old, stable], Platform::Linux64),
+            )
+            .expect("valid JSON");
+            known_good["versions"][0]["downloads"]["chrome-headless-shell"] = serde_json::json!([]);
+            known_good["versions"][1]["downloads"] =
+                test_support::downloads_json(old, Platform::Win64);
+            let known_good = serde_json::from_value::<KnownGoodVersions>(known_good)
+                .expect("valid known-good versions");
+            let last_known_good = serde_json::from_str::<LastKnownGoodVersions>(
+                &test_support::last_known_good_versions_json(
+                    &[
+                        (Channel::Canary, beta),
+                        (Channel::Stable, stable),
+                        (Channel::Beta, beta),
+                    ],
+                    Platform::Linux64,
+                ),
+            )
+            .expect("valid last-known-good versions");
+
+            let available = available_versions(&known_good, &last_known_good, Platform::Linux64);
+
+            assert_that!(available).contains_exactly([
+                AvailableVersion {
+                    version: stable,
+                    revision: Some(String::from("1")),
+                    channels: vec![Channel::Stable],
+                    chrome: true,
+                    chrome_headless_shell: true,
+                    chromedriver: true,
+                },
+                AvailableVersion {
+                    version: beta,
+                    revision: Some(String::from("1")),
+                    channels: vec![Channel::Beta, Channel::Canary],
+                    chrome: true,
+                    chrome_headless_shell: false,
+                    chromedriver: true,
+                },
+            ]);
+        }
+    }
+
+    #[test]
+    fn canonical_download_follows_chrome_for_testing_url_pattern() {
+        let v: Version = "135.0.7019.0".parse().expect("valid version literal");
+
+        assert_that!(
+            canonical_download(ChromeForTestingArtifact::ChromeHeadlessShell, v, Platform::Linux64)
+        )
+        .is_equal_to(Download {
+            platform: Platform::Linux64,
+            url: String::from(
+                "https://storage.googleapis.com/chrome-for-testing-public/135.0.7019.0/linux64/chrome-headless-shell-linux64.zip",
+            ),
+        });
+    }
 }
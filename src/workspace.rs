@@ -0,0 +1,113 @@
+use crate::ChromeForTestingManagerError;
+use rootcause::{Report, option_ext::OptionExt};
+use std::path::{Path, PathBuf};
+
+/// The root directory of the Cargo workspace the current process was started from.
+///
+/// Starts at [`search_start`]. See [`find_workspace_root`].
+pub(crate) fn workspace_root() -> Result<PathBuf, Report<ChromeForTestingManagerError>> {
+    let start = search_start();
+    start
+        .as_deref()
+        .and_then(find_workspace_root)
+        .context(ChromeForTestingManagerError::DetermineWorkspaceRoot { start })
+}
+
+/// The directory workspace-level files are searched from: `$CARGO_MANIFEST_DIR`, falling back
+/// to the current working directory.
+pub(crate) fn search_start() -> Option<PathBuf> {
+    std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .or_else(|| std::env::current_dir().ok())
+}
+
+/// The nearest file named `file_name` in `start` or any of its parents.
+pub(crate) fn find_upwards(start: &Path, file_name: &str) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(file_name))
+        .find(|path| path.is_file())
+}
+
+/// Walk up from `start` to the nearest directory whose `Cargo.toml` declares a `[workspace]`,
+/// like Cargo itself does.
+///
+/// Without any workspace manifest, the nearest directory containing a `Cargo.toml` is the root.
+fn find_workspace_root(start: &Path) -> Option<PathBuf> {
+    let mut package_root = None;
+    for dir in start.ancestors() {
+        let manifest = dir.join("Cargo.toml");
+        let Ok(contents) = std::fs::read_to_string(&manifest) else {
+            continue;
+        };
+        package_root.get_or_insert_with(|| dir.to_owned());
+        match contents.parse::<toml::Table>() {
+            Ok(table) if table.contains_key("workspace") => return Some(dir.to_owned()),
+            Ok(_) => {}
+            Err(err) => tracing::debug!("Ignoring unparsable manifest {manifest:?}: {err}"),
+        }
+    }
+    package_root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assertr::prelude::*;
+
+    #[test]
+    fn finds_the_workspace_manifest_of_a_member() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let member = dir.path().join("crates/member");
+        std::fs::create_dir_all(member.join("src"))?;
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/member\"]\n",
+        )?;
+        std::fs::write(member.join("Cargo.toml"), "[package]\nname = \"member\"\n")?;
+
+        assert_that!(find_workspace_root(&member.join("src")))
+            .is_equal_to(Some(dir.path().to_owned()));
+        Ok(())
+    }
+
+    #[test]
+    fn stops_at_the_nearest_workspace_manifest() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let nested = dir.path().join("vendor/nested");
+        std::fs::create_dir_all(nested.join("src"))?;
+        std::fs::write(dir.path().join("Cargo.toml"), "[workspace]\n")?;
+        std::fs::write(nested.join("Cargo.toml"), "[workspace]\n")?;
+
+        assert_that!(find_workspace_root(&nested.join("src"))).is_equal_to(Some(nested));
+        Ok(())
+    }
+
+    #[test]
+    fn finds_the_nearest_file_upwards() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let nested = dir.path().join("a/b");
+        std::fs::create_dir_all(&nested)?;
+        std::fs::write(dir.path().join("settings.toml"), "")?;
+        std::fs::write(dir.path().join("a/settings.toml"), "")?;
+
+        assert_that!(find_upwards(&nested, "settings.toml"))
+            .is_equal_to(Some(dir.path().join("a/settings.toml")));
+        assert_that!(find_upwards(&nested, "missing.toml")).is_none();
+        Ok(())
+    }
+
+    #[test]
+    fn falls_back_to_the_nearest_package_manifest() -> std::io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let package = dir.path().join("package");
+        std::fs::create_dir_all(package.join("src"))?;
+        std::fs::write(
+            package.join("Cargo.toml"),
+            "[package]\nname = \"package\"\n",
+        )?;
+
+        assert_that!(find_workspace_root(&package.join("src"))).is_equal_to(Some(package));
+        Ok(())
+    }
+}